@@ -22,7 +22,7 @@ pub fn main() {
 
     let attrs = client.send(operation).unwrap();
 
-    for v in attrs.get_group(DelimiterTag::PrinterAttributes).unwrap().values() {
+    for v in attrs.get_group(DelimiterTag::PrinterAttributes).unwrap() {
         println!("{}: {}", v.name(), v.value());
     }
 }
@@ -10,13 +10,13 @@ use std::io;
 use hyper::server::{Server, Request, Response, Handler};
 use ipp::parser::IppParser;
 use ipp::server::*;
-use ipp::{IppRequestResponse,IppHeader};
+use ipp::{IppRequestResponse,IppError};
 use ipp::request::IppRequestTrait;
 use ipp::consts::statuscode::*;
 use ipp::consts::tag::*;
 use ipp::consts::attribute::*;
 use ipp::consts::operation::Operation;
-use ipp::attribute::{IppAttribute,IppAttributeList};
+use ipp::attribute::IppAttribute;
 use ipp::value::IppValue;
 
 struct DummyServer {
@@ -87,13 +87,7 @@ impl DummyServer {
             ];
             IppAttribute::new(attr,
                               IppValue::ListOf(formats))
-        } else if attr == COMPRESSION_SUPPORTED {
-            let compressions = vec![
-                IppValue::Keyword("none".to_string())
-            ];
-            IppAttribute::new(attr,
-                              IppValue::ListOf(compressions))
-        } else if attr == URI_AUTHENTICATION_SUPPORTED {
+        } else if attr == COMPRESSION_SUPPORTED || attr == URI_AUTHENTICATION_SUPPORTED {
             let auths = vec![
                 IppValue::Keyword("none".to_string())
             ];
@@ -150,26 +144,14 @@ impl DummyServer {
     }
 }
 
-struct DummyRequest<'a, 'b: 'a> {
-    header: IppHeader,
-    attributes: IppAttributeList,
-    req: Request<'a, 'b>,
-}
-
-impl<'a, 'b> IppRequestTrait for DummyRequest<'a, 'b> {
-    fn header(&self) -> &IppHeader {
-        &self.header
-    }
-}
-
-impl<'b, 'c: 'b> IppServer<'b, 'c> for DummyServer {
-    type IppRequest = DummyRequest<'b, 'c>;
+impl<'b, 'c> IppServer<'b, 'c> for DummyServer {
+    type IppRequest = IppRequestResponse<'b>;
 
     fn print_job<'a>(&self, req: &mut Self::IppRequest) -> IppServerResult<'a> {
         println!("Print-Job");
         println!("{:?}", req.header());
-        println!("{:?}", req.attributes);
-        println!("");
+        println!("{:?}", req.attributes());
+        println!();
         let mut resp = IppRequestResponse::new_response(StatusCode::SuccessfulOK as u16,
                                                         req.header().request_id);
 
@@ -187,16 +169,18 @@ impl<'b, 'c: 'b> IppServer<'b, 'c> for DummyServer {
                                IppValue::Keyword("completed-successfully".to_string())));
 
         self.printing.store(true, atomic::Ordering::Relaxed);
-        let mut file = OpenOptions::new().write(true).create(true).open("printjob.dat").unwrap();
-        io::copy(&mut req.req, &mut file).unwrap();
+        let mut file = OpenOptions::new().write(true).create(true).truncate(true).open("printjob.dat").unwrap();
+        if let Some(payload) = req.payload() {
+            io::copy(payload, &mut file).unwrap();
+        }
         Ok(resp)
     }
 
     fn validate_job<'a>(&self, req: &mut Self::IppRequest) -> IppServerResult<'a> {
         println!("Validate-Job");
         println!("{:?}", req.header());
-        println!("{:?}", req.attributes);
-        println!("");
+        println!("{:?}", req.attributes());
+        println!();
         let resp = IppRequestResponse::new_response(StatusCode::SuccessfulOK as u16,
                                                         req.header().request_id);
 
@@ -220,7 +204,7 @@ impl<'b, 'c: 'b> IppServer<'b, 'c> for DummyServer {
     }
 
     fn get_printer_attributes<'a>(&self, req: &mut Self::IppRequest) -> IppServerResult<'a> {
-        const SUPPORTED_ATTRIBUTES : [&'static str; 23] = [
+        const SUPPORTED_ATTRIBUTES : [&str; 23] = [
             PRINTER_URI_SUPPORTED,
             URI_SECURITY_SUPPORTED,
             URI_AUTHENTICATION_SUPPORTED,
@@ -250,7 +234,7 @@ impl<'b, 'c: 'b> IppServer<'b, 'c> for DummyServer {
         let mut resp = IppRequestResponse::new_response(StatusCode::SuccessfulOK as u16,
                                                         req.header().request_id);
         let mut requested_attributes : Vec<&str> = vec![];
-        if let Some(attr) = req.attributes.get(DelimiterTag::OperationAttributes, REQUESTED_ATTRIBUTES) {
+        if let Some(attr) = req.attributes().get(DelimiterTag::OperationAttributes, REQUESTED_ATTRIBUTES) {
             match *attr.value() {
                 IppValue::Keyword(ref x) => {
                     requested_attributes = vec![x];
@@ -271,7 +255,7 @@ impl<'b, 'c: 'b> IppServer<'b, 'c> for DummyServer {
             }
         };
 
-        let attribute_list = if requested_attributes.len() == 0 {
+        let attribute_list = if requested_attributes.is_empty() {
             supported_attributes
         } else {
             &requested_attributes[..]
@@ -280,7 +264,7 @@ impl<'b, 'c: 'b> IppServer<'b, 'c> for DummyServer {
         for attr in attribute_list {
             if supported_attributes.contains(attr) {
                 resp.set_attribute(DelimiterTag::PrinterAttributes,
-                                   self.get_printer_attribute(&attr));
+                                   self.get_printer_attribute(attr));
             } else {
                 println!("Unsupported attribute {}", attr);
             }
@@ -292,17 +276,20 @@ impl<'b, 'c: 'b> IppServer<'b, 'c> for DummyServer {
 
 impl Handler for DummyServer {
     fn handle(&self, mut req: Request, res: Response) {
-        let ippreq = {
-            let mut parser = IppParser::new(&mut req);
-            IppRequestResponse::from_parser(&mut parser).unwrap()
-        };
-        let mut dummy_req = DummyRequest {
-            header: ippreq.header().clone(),
-            attributes: ippreq.attributes().clone(),
-            req: req,
+        let mut ippreq = match IppRequestResponse::from_parser(IppParser::new(&mut req)) {
+            Ok(ippreq) => ippreq,
+            Err(error) => {
+                let status = match error {
+                    IppError::ParseError(ref e) => e.kind.status_code(),
+                    _ => StatusCode::ClientErrorBadRequest
+                };
+                let mut ippresp = IppRequestResponse::new_response(status as u16, 0);
+                let mut res_streaming = res.start().unwrap();
+                ippresp.write(&mut res_streaming).expect("Failed to write response");
+                return;
+            }
         };
-
-        let mut ippresp = match self.ipp_handle_request(&mut dummy_req) {
+        let mut ippresp = match self.ipp_handle_request(&mut ippreq) {
             Ok(response) => response,
             Err(ipp_error) =>
                 IppRequestResponse::new_response(ipp_error as u16,
@@ -4,6 +4,7 @@ extern crate env_logger;
 use std::env;
 use std::process::exit;
 use std::fs::File;
+use std::convert::TryFrom;
 
 use ipp::{IppClient, IppValue, GetPrinterAttributes, CreateJob, SendDocument};
 use ipp::consts::tag::DelimiterTag;
@@ -39,20 +40,17 @@ fn main() {
 
     let create_op = CreateJob::new(Some("multi-doc"));
     let attrs = client.send(create_op).unwrap();
-    let job_id = match *attrs.get(DelimiterTag::JobAttributes, JOB_ID).unwrap().value() {
-        IppValue::Integer(id) => id,
-        _ => panic!("invalid value")
-    };
+    let job_id = i32::try_from(attrs.get(DelimiterTag::JobAttributes, JOB_ID).unwrap()).unwrap();
     println!("job id: {}", job_id);
 
     for (i, item) in args.iter().enumerate().skip(2) {
         let last = i >= (args.len() - 1);
         println!("Sending {}, last: {}", item, last);
-        let mut f = File::open(&item).unwrap();
+        let f = File::open(item).unwrap();
 
-        let send_op = SendDocument::new(job_id, &mut f, &env::var("USER").unwrap(), last);
+        let send_op = SendDocument::new(job_id, f, &env::var("USER").unwrap(), last);
         let send_attrs = client.send(send_op).unwrap();
-        for v in send_attrs.get_group(DelimiterTag::JobAttributes).unwrap().values() {
+        for v in send_attrs.get_group(DelimiterTag::JobAttributes).unwrap() {
             println!("{}: {}", v.name(), v.value());
         }
     }
@@ -19,9 +19,9 @@ pub fn main() {
     }
 
     let client = IppClient::new(&args[1]);
-    let mut f = File::open(&args[2]).unwrap();
+    let f = File::open(&args[2]).unwrap();
     let mut operation = PrintJob::new(
-        &mut f,
+        f,
         &env::var("USER").unwrap(),
         Some(&args[1])
     );
@@ -43,7 +43,7 @@ pub fn main() {
 
     let attrs = client.send(operation).unwrap();
 
-    for v in attrs.get_group(DelimiterTag::JobAttributes).unwrap().values() {
+    for v in attrs.get_group(DelimiterTag::JobAttributes).unwrap() {
         println!("{}: {}", v.name(), v.value());
     }
 }
@@ -1,22 +1,38 @@
 //!
 //! Attribute-related structs
 //!
-use std::collections::HashMap;
 use std::io::Write;
+use std::slice;
 use byteorder::{BigEndian, WriteBytesExt};
 
 use ::Result;
-use value::IppValue;
+use value::{self, IppValue, MAX_LENGTH, MAX_NAME_LENGTH};
+use charset::IppCharset;
 use consts::tag::*;
 use consts::attribute::*;
 
-const HEADER_ATTRS: [&'static str; 3] = [
+// RFC 8011 4.1.4: charset and natural language come first, followed by the operation target
+const HEADER_ATTRS: [&str; 7] = [
     ATTRIBUTES_CHARSET,
     ATTRIBUTES_NATURAL_LANGUAGE,
-    PRINTER_URI];
+    SYSTEM_URI,
+    PRINTER_URI,
+    JOB_URI,
+    JOB_ID,
+    DOCUMENT_NUMBER];
 
-fn is_header_attr(attr: &str) -> bool {
-    HEADER_ATTRS.into_iter().any(|&at| at == attr)
+// position of the attribute in the operation group, header attributes first
+fn header_rank(attr: &str) -> usize {
+    HEADER_ATTRS.iter().position(|hdr| *hdr == attr).unwrap_or(HEADER_ATTRS.len())
+}
+
+// position of the group in a message: operation attributes, unsupported attributes, the rest
+fn group_rank(tag: DelimiterTag) -> u8 {
+    match tag {
+        DelimiterTag::OperationAttributes => 0,
+        DelimiterTag::UnsupportedAttributes => 1,
+        _ => 2
+    }
 }
 
 /// `IppAttribute` represents an IPP attribute
@@ -33,8 +49,8 @@ impl IppAttribute {
     ///
     /// * `name` - Attribute name<br/>
     /// * `value` - Attribute value<br/>
-    pub fn new(name: &str, value: IppValue) -> IppAttribute {
-        IppAttribute {name: name.to_string(), value: value}
+    pub fn new<V: Into<IppValue>>(name: &str, value: V) -> IppAttribute {
+        IppAttribute {name: name.to_string(), value: value.into()}
     }
 
     /// Return attribute name
@@ -47,11 +63,35 @@ impl IppAttribute {
         &self.value
     }
 
+    /// Check that the attribute can be encoded: the name and every value must fit
+    /// into the 16-bit length fields of the wire format
+    pub fn check_encodable(&self) -> Result<()> {
+        value::check_length(&self.name, self.name.len(), MAX_LENGTH)?;
+        self.value.check_encodable().map_err(|e| value::with_attribute(e, &self.name))
+    }
+
+    /// Check the attribute name and values against the RFC 8011 maximum lengths
+    pub fn check_limits(&self) -> Result<()> {
+        value::check_length(&self.name, self.name.len(), MAX_NAME_LENGTH)?;
+        self.value.check_limits().map_err(|e| value::with_attribute(e, &self.name))
+    }
+
     /// Serialize attribute into binary stream
-    pub fn write(&self, writer: &mut Write) -> Result<usize> {
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize> {
+        self.write_with_charset(writer, IppCharset::Utf8)
+    }
+
+    /// Serialize attribute into binary stream, text and name values are encoded using the given charset
+    pub fn write_with_charset(&self, writer: &mut dyn Write, charset: IppCharset) -> Result<usize> {
+        self.check_encodable()?;
+        self.write_unchecked(writer, charset)
+    }
+
+    /// Serialize attribute which already passed `check_encodable`
+    fn write_unchecked(&self, writer: &mut dyn Write, charset: IppCharset) -> Result<usize> {
         let mut retval = 0;
 
-        writer.write_u8(self.value.to_tag() as u8)?;
+        writer.write_u8(self.value.to_tag())?;
         retval += 1;
 
         writer.write_u16::<BigEndian>(self.name.len() as u16)?;
@@ -60,16 +100,132 @@ impl IppAttribute {
         writer.write_all(self.name.as_bytes())?;
         retval += self.name.len();
 
-        retval += self.value.write(writer)?;
+        retval += self.value.write_unchecked(writer, charset)?;
+
+        Ok(retval)
+    }
+}
+
+/// Attribute group: ordered attributes which follow a single delimiter tag
+#[derive(Clone, Debug)]
+pub struct IppAttributeGroup {
+    /// Delimiter tag of the group
+    tag: DelimiterTag,
+    /// Attributes in wire order
+    attributes: Vec<IppAttribute>
+}
+
+impl IppAttributeGroup {
+    /// Create empty attribute group
+    ///
+    /// * `tag` - delimiter tag of the group<br/>
+    pub fn new(tag: DelimiterTag) -> IppAttributeGroup {
+        IppAttributeGroup {tag, attributes: Vec::new()}
+    }
+
+    /// Return delimiter tag of the group
+    pub fn tag(&self) -> DelimiterTag {
+        self.tag
+    }
+
+    /// Return attributes of the group in wire order
+    pub fn attributes(&self) -> &[IppAttribute] {
+        &self.attributes
+    }
+
+    /// Return iterator over the attributes of the group
+    pub fn iter(&self) -> slice::Iter<'_, IppAttribute> {
+        self.attributes.iter()
+    }
+
+    /// Return number of attributes in the group
+    pub fn len(&self) -> usize {
+        self.attributes.len()
+    }
+
+    /// Check whether the group has no attributes
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty()
+    }
+
+    /// Get attribute by name, the first one if the name occurs more than once
+    pub fn get(&self, name: &str) -> Option<&IppAttribute> {
+        self.attributes.iter().find(|attr| attr.name() == name)
+    }
+
+    /// Add attribute to the group. Attribute with the same name is replaced in place.
+    /// In the operation group `attributes-charset`, `attributes-natural-language` and
+    /// the target attributes are placed before the others, RFC 8011 section 4.1.4.
+    pub fn add(&mut self, attribute: IppAttribute) {
+        if let Some(index) = self.attributes.iter().position(|attr| attr.name() == attribute.name()) {
+            self.attributes[index] = attribute;
+            return;
+        }
+        let index = if self.tag == DelimiterTag::OperationAttributes {
+            let rank = header_rank(attribute.name());
+            self.attributes.iter().position(|attr| header_rank(attr.name()) > rank).unwrap_or(self.attributes.len())
+        } else {
+            self.attributes.len()
+        };
+        self.attributes.insert(index, attribute);
+    }
+
+    /// Append attribute to the end of the group, even if an attribute with the same name exists
+    pub fn push(&mut self, attribute: IppAttribute) {
+        self.attributes.push(attribute);
+    }
+
+    /// Serialize attribute group into binary stream
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize> {
+        self.write_with_charset(writer, IppCharset::Utf8)
+    }
+
+    /// Check that every attribute of the group can be encoded
+    pub fn check_encodable(&self) -> Result<()> {
+        for attr in &self.attributes {
+            attr.check_encodable()?;
+        }
+        Ok(())
+    }
+
+    /// Serialize attribute group into binary stream, text and name values are encoded using the given charset.
+    /// Nothing is written if any attribute does not fit into the wire format.
+    pub fn write_with_charset(&self, writer: &mut dyn Write, charset: IppCharset) -> Result<usize> {
+        self.check_encodable()?;
+        self.write_unchecked(writer, charset)
+    }
+
+    /// Serialize attribute group which already passed `check_encodable`
+    fn write_unchecked(&self, writer: &mut dyn Write, charset: IppCharset) -> Result<usize> {
+        writer.write_u8(self.tag as u8)?;
+
+        let mut retval = 1;
+
+        for attr in &self.attributes {
+            retval += attr.write_unchecked(writer, charset)?;
+        }
 
         Ok(retval)
     }
 }
 
-/// Attribute list indexed by group and name
+impl<'a> IntoIterator for &'a IppAttributeGroup {
+    type Item = &'a IppAttribute;
+    type IntoIter = slice::Iter<'a, IppAttribute>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.attributes.iter()
+    }
+}
+
+/// Attribute list: attribute groups in wire order.
+///
+/// The same delimiter tag may occur several times, for example one job group per job
+/// in a Get-Jobs response. Groups and attributes are written in the order they are kept:
+/// `add` places them in RFC 8011 order, `add_group` and the parser keep the order given.
 #[derive(Clone, Default, Debug)]
 pub struct IppAttributeList {
-    attributes: HashMap<DelimiterTag, HashMap<String, IppAttribute>>
+    groups: Vec<IppAttributeGroup>
 }
 
 impl IppAttributeList {
@@ -78,56 +234,232 @@ impl IppAttributeList {
         IppAttributeList::default()
     }
 
-    /// Add attribute to the list
+    /// Add attribute to the first group with the given tag, creating the group if needed.
+    /// A new operation group is placed first and a new unsupported group after it,
+    /// other new groups are appended.
     ///
     /// * `group` - delimiter group<br/>
     /// * `attribute` - attribute to add<br/>
     pub fn add(&mut self, group: DelimiterTag, attribute: IppAttribute) {
-        self.attributes.entry(group).or_insert_with(HashMap::new);
-        let mut opt = self.attributes.get_mut(&group).unwrap();
-        opt.insert(attribute.name().to_string(), attribute);
+        match self.groups.iter().position(|g| g.tag() == group) {
+            Some(index) => self.groups[index].add(attribute),
+            None => {
+                let mut new_group = IppAttributeGroup::new(group);
+                new_group.add(attribute);
+                let rank = group_rank(group);
+                let index = self.groups.iter().position(|g| group_rank(g.tag()) > rank).unwrap_or(self.groups.len());
+                self.groups.insert(index, new_group);
+            }
+        }
     }
 
-    /// Get attribute from the list
-    pub fn get<'a>(&'a self, group: DelimiterTag, name: &str) -> Option<&IppAttribute> {
-        self.attributes.get(&group).map_or(None, |attrs| attrs.get(name))
+    /// Append a new group to the end of the list, even if a group with the same tag exists
+    pub fn add_group(&mut self, group: IppAttributeGroup) {
+        self.groups.push(group);
     }
 
-    /// Get attribute list for a group
-    pub fn get_group(&self, group: DelimiterTag) -> Option<&HashMap<String, IppAttribute>> {
-        self.attributes.get(&group)
+    /// Get attribute from the first group with the given tag which contains it
+    pub fn get(&self, group: DelimiterTag, name: &str) -> Option<&IppAttribute> {
+        self.groups_of(group).filter_map(|g| g.get(name)).next()
     }
 
-    /// Serialize attribute list into binary stream
-    pub fn write(&self, writer: &mut Write) -> Result<usize> {
-        // first send the header attributes
-        writer.write_u8(DelimiterTag::OperationAttributes as u8)?;
+    /// Get the first group with the given tag
+    pub fn get_group(&self, group: DelimiterTag) -> Option<&IppAttributeGroup> {
+        self.groups_of(group).next()
+    }
 
-        let mut retval = 1;
+    /// Get all groups with the given tag in wire order
+    pub fn groups_of(&self, group: DelimiterTag) -> impl Iterator<Item = &IppAttributeGroup> {
+        self.groups.iter().filter(move |g| g.tag() == group)
+    }
 
-        for hdr in &HEADER_ATTRS {
-            if let Some(attr) = self.get(DelimiterTag::OperationAttributes, hdr) {
-                retval += attr.write(writer)?
+    /// Get all groups in wire order
+    pub fn groups(&self) -> &[IppAttributeGroup] {
+        &self.groups
+    }
+
+    /// Check all attributes against the RFC 8011 maximum lengths of their syntaxes.
+    /// Call it before `write` to reject oversized values before anything is sent.
+    pub fn check_limits(&self) -> Result<()> {
+        for group in &self.groups {
+            for attr in group {
+                attr.check_limits()?;
             }
         }
+        Ok(())
+    }
 
-        // now the rest
-        for hdr in &[DelimiterTag::OperationAttributes, DelimiterTag::JobAttributes, DelimiterTag::PrinterAttributes] {
-            let group = *hdr;
-            if let Some(attrs) = self.get_group(group) {
-                if group != DelimiterTag::OperationAttributes {
-                    writer.write_u8(group as u8)?;
-                    retval += 1;
-                }
-                for (_, attr) in attrs.iter().filter(
-                    |&(_, v)| group != DelimiterTag::OperationAttributes || !is_header_attr(v.name())) {
-                    retval += attr.write(writer)?;
-                }
-            }
+    /// Check that every attribute can be encoded
+    pub fn check_encodable(&self) -> Result<()> {
+        for group in &self.groups {
+            group.check_encodable()?;
+        }
+        Ok(())
+    }
+
+    /// Charset of text and name values as given by the `attributes-charset` operation attribute,
+    /// utf-8 if it is missing or not supported
+    pub fn charset(&self) -> IppCharset {
+        match self.get(DelimiterTag::OperationAttributes, ATTRIBUTES_CHARSET).map(|attr| attr.value()) {
+            Some(IppValue::Charset(name)) => IppCharset::from_name(name).unwrap_or_default(),
+            _ => IppCharset::Utf8
+        }
+    }
+
+    /// Serialize attribute list into binary stream in the order of the groups,
+    /// text and name values are encoded using the charset named by `attributes-charset`.
+    /// Nothing is written if any attribute does not fit into the wire format.
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize> {
+        self.check_encodable()?;
+
+        let mut retval = 0;
+
+        let charset = self.charset();
+        for group in &self.groups {
+            retval += group.write_unchecked(writer, charset)?;
         }
+
         writer.write_u8(DelimiterTag::EndOfAttributes as u8)?;
         retval += 1;
 
         Ok(retval)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::*;
+    use parser::IppParser;
+    use IppHeader;
+
+    fn keyword(name: &str, value: &str) -> IppAttribute {
+        IppAttribute::new(name, IppValue::Keyword(value.to_string()))
+    }
+
+    fn write_message(attributes: &IppAttributeList) -> Vec<u8> {
+        let mut data = Vec::new();
+        IppHeader::new(0x0101, 0, 1).write(&mut data).unwrap();
+        attributes.write(&mut data).unwrap();
+        data
+    }
+
+    fn parse(data: &[u8]) -> IppAttributeList {
+        let mut cursor = Cursor::new(data);
+        IppParser::new(&mut cursor).parse().unwrap().into_parts().1
+    }
+
+    fn names(group: &IppAttributeGroup) -> Vec<&str> {
+        group.iter().map(|attr| attr.name()).collect()
+    }
+
+    #[test]
+    fn test_header_attributes_first() {
+        let mut attributes = IppAttributeList::new();
+        attributes.add(DelimiterTag::OperationAttributes, keyword("requesting-user-name", "user"));
+        attributes.add(DelimiterTag::OperationAttributes, IppAttribute::new(PRINTER_URI, IppValue::Uri("ipp://localhost".to_string())));
+        attributes.add(DelimiterTag::OperationAttributes, IppAttribute::new(ATTRIBUTES_NATURAL_LANGUAGE,
+                                                                            IppValue::NaturalLanguage("en".to_string())));
+        attributes.add(DelimiterTag::OperationAttributes, keyword("document-format", "application/pdf"));
+        attributes.add(DelimiterTag::OperationAttributes, IppAttribute::new(ATTRIBUTES_CHARSET,
+                                                                            IppValue::Charset("utf-8".to_string())));
+
+        let data = write_message(&attributes);
+        let parsed = parse(&data);
+        assert_eq!(names(&parsed.groups()[0]), [ATTRIBUTES_CHARSET, ATTRIBUTES_NATURAL_LANGUAGE, PRINTER_URI,
+                                               "requesting-user-name", "document-format"]);
+        assert_eq!(write_message(&attributes), data);
+    }
+
+    #[test]
+    fn test_group_order() {
+        let mut attributes = IppAttributeList::new();
+        for job in &["1", "2", "3"] {
+            let mut group = IppAttributeGroup::new(DelimiterTag::JobAttributes);
+            group.add(keyword("job-id", job));
+            attributes.add_group(group);
+        }
+        attributes.add(DelimiterTag::UnsupportedAttributes, keyword("sides", "one-sided"));
+        attributes.add(DelimiterTag::PrinterAttributes, keyword("printer-state", "idle"));
+        attributes.add(DelimiterTag::OperationAttributes, IppAttribute::new(ATTRIBUTES_CHARSET,
+                                                                            IppValue::Charset("utf-8".to_string())));
+
+        let data = write_message(&attributes);
+        let parsed = parse(&data);
+        let tags: Vec<_> = parsed.groups().iter().map(|group| group.tag()).collect();
+        assert_eq!(tags, [DelimiterTag::OperationAttributes, DelimiterTag::UnsupportedAttributes,
+                          DelimiterTag::JobAttributes, DelimiterTag::JobAttributes, DelimiterTag::JobAttributes,
+                          DelimiterTag::PrinterAttributes]);
+        let jobs: Vec<_> = parsed.groups_of(DelimiterTag::JobAttributes)
+            .map(|group| format!("{}", group.get("job-id").unwrap().value()))
+            .collect();
+        assert_eq!(jobs, ["1", "2", "3"]);
+        assert_eq!(write_message(&parsed), data);
+    }
+
+    #[test]
+    fn test_parsed_order_is_kept() {
+        let mut data = Vec::new();
+        IppHeader::new(0x0101, 0, 1).write(&mut data).unwrap();
+        let mut group = |tag: DelimiterTag, attributes: &[IppAttribute]| {
+            data.push(tag as u8);
+            for attr in attributes {
+                attr.write(&mut data).unwrap();
+            }
+        };
+        group(DelimiterTag::OperationAttributes, &[
+            keyword("requesting-user-name", "user"),
+            IppAttribute::new(ATTRIBUTES_CHARSET, IppValue::Charset("utf-8".to_string()))]);
+        group(DelimiterTag::JobAttributes, &[keyword("job-id", "1")]);
+        group(DelimiterTag::PrinterAttributes, &[keyword("printer-state", "idle")]);
+        group(DelimiterTag::JobAttributes, &[keyword("job-id", "2")]);
+        group(DelimiterTag::UnsupportedAttributes, &[keyword("sides", "one-sided")]);
+        data.push(DelimiterTag::EndOfAttributes as u8);
+
+        assert_eq!(write_message(&parse(&data)), data);
+    }
+
+    #[test]
+    fn test_extended_groups() {
+        let mut attributes = IppAttributeList::new();
+        attributes.add(DelimiterTag::SubscriptionAttributes, keyword("notify-events", "job-completed"));
+        attributes.add(DelimiterTag::OperationAttributes, IppAttribute::new(ATTRIBUTES_CHARSET,
+                                                                            IppValue::Charset("utf-8".to_string())));
+        let mut data = write_message(&attributes);
+        assert_eq!(data[8], DelimiterTag::OperationAttributes as u8);
+        assert!(data.contains(&(DelimiterTag::SubscriptionAttributes as u8)));
+
+        // reserved delimiters start groups which are kept as they are
+        data.pop();
+        for tag in &[DelimiterTag::EventNotificationAttributes, DelimiterTag::SystemAttributes, DelimiterTag::Reserved0B] {
+            data.push(*tag as u8);
+            keyword("vendor-attribute", "value").write(&mut data).unwrap();
+        }
+        data.push(DelimiterTag::EndOfAttributes as u8);
+
+        let parsed = parse(&data);
+        let tags: Vec<_> = parsed.groups().iter().map(|group| group.tag()).collect();
+        assert_eq!(tags, [DelimiterTag::OperationAttributes, DelimiterTag::SubscriptionAttributes,
+                          DelimiterTag::EventNotificationAttributes, DelimiterTag::SystemAttributes,
+                          DelimiterTag::Reserved0B]);
+        assert!(parsed.get(DelimiterTag::Reserved0B, "vendor-attribute").is_some());
+        assert_eq!(write_message(&parsed), data);
+    }
+
+    #[test]
+    fn test_no_partial_write() {
+        let mut group = IppAttributeGroup::new(DelimiterTag::JobAttributes);
+        group.add(keyword("job-name", "name"));
+        group.add(IppAttribute::new("job-message", IppValue::TextWithoutLanguage("x".repeat(0x10000))));
+
+        let mut data = Vec::new();
+        assert!(group.write(&mut data).is_err());
+        assert!(data.is_empty());
+
+        let mut attributes = IppAttributeList::new();
+        attributes.add(DelimiterTag::OperationAttributes, keyword("document-format", "application/pdf"));
+        attributes.add_group(group);
+        assert!(attributes.write(&mut data).is_err());
+        assert!(data.is_empty());
+    }
+}
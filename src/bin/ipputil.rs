@@ -9,36 +9,34 @@ use ipp::{IppClient, IppAttribute, IppValue, PrintJob, GetPrinterAttributes, Ipp
 use ipp::consts::tag::DelimiterTag;
 
 fn do_print(args: &[String]) -> Result<(), IppError> {
-    let mut f = File::open(&args[3])?;
+    let f = File::open(&args[3])?;
 
     let client = IppClient::new(&args[2]);
 
     let mut operation = PrintJob::new(
-        &mut f,
+        f,
         &env::var("USER").unwrap_or_else(|_| String::new()),
         Some(&args[1])
     );
 
+    // attributes are given as name=value with inferred syntax or name:syntax=value
     for arg in &args[4..] {
-        let mut kv = arg.split('=');
-        if let Some(k) = kv.next() {
-            if let Some(v) = kv.next() {
-                let value = if let Ok(iv) = v.parse::<i32>() {
-                    IppValue::Integer(iv)
-                } else if v == "true" || v == "false" {
-                    IppValue::Boolean(v == "true")
-                } else {
-                    IppValue::Keyword(v.to_string())
-                };
-                operation.add_attribute(IppAttribute::new(k, value));
-            }
+        let mut kv = arg.splitn(2, '=');
+        if let (Some(k), Some(v)) = (kv.next(), kv.next()) {
+            let mut ks = k.splitn(2, ':');
+            let name = ks.next().unwrap_or(k);
+            let value = match ks.next() {
+                Some(syntax) => IppValue::parse(syntax, v)?,
+                None => IppValue::infer(v)?
+            };
+            operation.add_attribute(IppAttribute::new(name, value));
         }
     }
 
     let attrs = client.send(operation)?;
 
     if let Some(group) = attrs.get_group(DelimiterTag::JobAttributes) {
-        for v in group.values() {
+        for v in group.iter() {
             println!("{}: {}", v.name(), v.value());
         }
     }
@@ -52,7 +50,7 @@ fn do_status(args: &[String]) -> Result<(), IppError> {
     let attrs = client.send(operation)?;
 
     if let Some(group) = attrs.get_group(DelimiterTag::PrinterAttributes) {
-        let mut values: Vec<_> = group.values().collect();
+        let mut values: Vec<_> = group.iter().collect();
         values.sort_by(|&a, &b| a.name().cmp(b.name()));
         for v in &values {
             println!("{}: {}", v.name(), v.value());
@@ -63,7 +61,7 @@ fn do_status(args: &[String]) -> Result<(), IppError> {
 
 fn usage(prog: &str) {
     println!("Usage: {} status uri [attr...]", prog);
-    println!("       {} print uri filename [attr=value]", prog);
+    println!("       {} print uri filename [attr[:syntax]=value]", prog);
     println!("\nSupported uri schemes: http, ipp");
 }
 
@@ -80,7 +78,7 @@ pub fn main() {
     match &args[1][..] {
         "status" => {
             if let Err(err) = do_status(&args) {
-                println!("{:?}", err);
+                println!("ERROR: {}", err);
                 exit(2);
             }
 
@@ -91,7 +89,7 @@ pub fn main() {
                 exit(1);
             }
             if let Err(err) = do_print(&args) {
-                println!("{:?}", err);
+                println!("ERROR: {}", err);
                 exit(2);
             }
         }
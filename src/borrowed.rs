@@ -0,0 +1,528 @@
+//!
+//! Zero-copy decoding of IPP messages
+//!
+//! Names, string values and binary values borrow from the input buffer. Strings are copied
+//! only when they are not valid UTF-8 or the message uses another charset for non-ASCII text.
+//! Use `to_owned()` to convert into the owned types.
+//!
+use std::borrow::Cow;
+use byteorder::{BigEndian, ByteOrder};
+
+use enum_primitive::FromPrimitive;
+
+use ::{Result, IppError, IppHeader};
+use attribute::{IppAttribute, IppAttributeGroup, IppAttributeList};
+use charset::IppCharset;
+use consts::tag::*;
+use datetime::IppDateTime;
+use parser::{DecodeValue, ParseErrorKind, ParseLimits, ParseMode, ParseState, StateValue};
+use value::{self, IppValue};
+
+// decode text, borrowing when the bytes mean the same in UTF-8
+fn decode(data: &[u8], charset: IppCharset) -> Cow<'_, str> {
+    match charset {
+        IppCharset::Utf8 => String::from_utf8_lossy(data),
+        _ if data.is_ascii() => String::from_utf8_lossy(data),
+        _ => Cow::Owned(charset.decode(data))
+    }
+}
+
+/// IPP value borrowing from the message buffer, see `IppValue` for the variants
+#[derive(Clone, Debug)]
+pub enum IppValueRef<'a> {
+    Integer(i32),
+    Enum(i32),
+    OctetString(&'a [u8]),
+    TextWithoutLanguage(Cow<'a, str>),
+    NameWithoutLanguage(Cow<'a, str>),
+    TextWithLanguage { language: Cow<'a, str>, text: Cow<'a, str> },
+    NameWithLanguage { language: Cow<'a, str>, name: Cow<'a, str> },
+    Charset(Cow<'a, str>),
+    NaturalLanguage(Cow<'a, str>),
+    Uri(Cow<'a, str>),
+    RangeOfInteger(i32, i32),
+    Boolean(bool),
+    Keyword(Cow<'a, str>),
+    ListOf(Vec<IppValueRef<'a>>),
+    Collection(Vec<(Cow<'a, str>, IppValueRef<'a>)>),
+    MimeMediaType(Cow<'a, str>),
+    DateTime(IppDateTime),
+    MemberAttrName(Cow<'a, str>),
+    Resolution(i32, i32, i8),
+    UriScheme(Cow<'a, str>),
+    Unsupported,
+    Unknown,
+    NoValue,
+    NotSettable,
+    DeleteAttribute,
+    AdminDefine,
+    Extension(u32, &'a [u8]),
+    Other(u8, &'a [u8]),
+}
+
+impl<'a> IppValueRef<'a> {
+    /// Decode value from the bytes of its value field, like `IppValue::from_bytes_with_charset`
+    pub fn from_bytes(vtag: u8, data: &'a [u8], charset: IppCharset) -> Result<IppValueRef<'a>> {
+        let ipptag = match ValueTag::from_u8(vtag) {
+            Some(x) => x,
+            None => return Ok(IppValueRef::Other(vtag, data))
+        };
+
+        if let Some(expected) = value::fixed_length(vtag) {
+            if data.len() != expected {
+                return Ok(IppValueRef::Other(vtag, data));
+            }
+        }
+
+        let text = || decode(data, charset);
+        let ascii = || String::from_utf8_lossy(data);
+        let retval = match ipptag {
+            ValueTag::Integer => IppValueRef::Integer(BigEndian::read_i32(data)),
+            ValueTag::Enum => IppValueRef::Enum(BigEndian::read_i32(data)),
+            ValueTag::Boolean => IppValueRef::Boolean(data[0] != 0),
+            ValueTag::RangeOfInteger => IppValueRef::RangeOfInteger(BigEndian::read_i32(data), BigEndian::read_i32(&data[4..])),
+            ValueTag::Resolution => IppValueRef::Resolution(BigEndian::read_i32(data), BigEndian::read_i32(&data[4..]), data[8] as i8),
+            ValueTag::DateTime => match IppDateTime::from_bytes(data) {
+                Ok(dt) => IppValueRef::DateTime(dt),
+                Err(_) => IppValueRef::Other(vtag, data)
+            },
+            ValueTag::OctectStringUnspecified => IppValueRef::OctetString(data),
+            ValueTag::TextWithoutLanguage => IppValueRef::TextWithoutLanguage(text()),
+            ValueTag::NameWithoutLanguage => IppValueRef::NameWithoutLanguage(text()),
+            ValueTag::TextWithLanguage | ValueTag::NameWithLanguage => {
+                let (language, text) = value::split_with_language(data)?;
+                let (language, text) = (String::from_utf8_lossy(language), decode(text, charset));
+                if ipptag == ValueTag::TextWithLanguage {
+                    IppValueRef::TextWithLanguage { language, text }
+                } else {
+                    IppValueRef::NameWithLanguage { language, name: text }
+                }
+            }
+            ValueTag::Charset => IppValueRef::Charset(ascii()),
+            ValueTag::NaturalLanguage => IppValueRef::NaturalLanguage(ascii()),
+            ValueTag::Uri => IppValueRef::Uri(ascii()),
+            ValueTag::UriScheme => IppValueRef::UriScheme(ascii()),
+            ValueTag::Keyword => IppValueRef::Keyword(ascii()),
+            ValueTag::MimeMediaType => IppValueRef::MimeMediaType(ascii()),
+            ValueTag::MemberAttrName => IppValueRef::MemberAttrName(ascii()),
+            ValueTag::Unsupported => IppValueRef::Unsupported,
+            ValueTag::Unknown => IppValueRef::Unknown,
+            ValueTag::NoValue => IppValueRef::NoValue,
+            ValueTag::NotSettable => IppValueRef::NotSettable,
+            ValueTag::DeleteAttribute => IppValueRef::DeleteAttribute,
+            ValueTag::AdminDefine => IppValueRef::AdminDefine,
+            ValueTag::Extension => {
+                if data.len() < 4 {
+                    return Err(IppError::AttributeError("Invalid value length for extension tag".to_string()));
+                }
+                IppValueRef::Extension(BigEndian::read_u32(data), &data[4..])
+            }
+            _ => IppValueRef::Other(vtag, data)
+        };
+        Ok(retval)
+    }
+
+    /// Return tag of the value, the first value's tag for 1setOf
+    pub fn to_tag(&self) -> u8 {
+        let tag = match *self {
+            IppValueRef::Integer(_) => ValueTag::Integer,
+            IppValueRef::Enum(_) => ValueTag::Enum,
+            IppValueRef::RangeOfInteger(_, _) => ValueTag::RangeOfInteger,
+            IppValueRef::Boolean(_) => ValueTag::Boolean,
+            IppValueRef::Keyword(_) => ValueTag::Keyword,
+            IppValueRef::OctetString(_) => ValueTag::OctectStringUnspecified,
+            IppValueRef::TextWithoutLanguage(_) => ValueTag::TextWithoutLanguage,
+            IppValueRef::NameWithoutLanguage(_) => ValueTag::NameWithoutLanguage,
+            IppValueRef::TextWithLanguage { .. } => ValueTag::TextWithLanguage,
+            IppValueRef::NameWithLanguage { .. } => ValueTag::NameWithLanguage,
+            IppValueRef::Charset(_) => ValueTag::Charset,
+            IppValueRef::NaturalLanguage(_) => ValueTag::NaturalLanguage,
+            IppValueRef::Uri(_) => ValueTag::Uri,
+            IppValueRef::MimeMediaType(_) => ValueTag::MimeMediaType,
+            IppValueRef::ListOf(ref list) => return list.first().map_or(ValueTag::NoValue as u8, IppValueRef::to_tag),
+            IppValueRef::Collection(_) => ValueTag::BegCollection,
+            IppValueRef::DateTime(_) => ValueTag::DateTime,
+            IppValueRef::MemberAttrName(_) => ValueTag::MemberAttrName,
+            IppValueRef::Resolution(_, _, _) => ValueTag::Resolution,
+            IppValueRef::UriScheme(_) => ValueTag::UriScheme,
+            IppValueRef::Unsupported => ValueTag::Unsupported,
+            IppValueRef::Unknown => ValueTag::Unknown,
+            IppValueRef::NoValue => ValueTag::NoValue,
+            IppValueRef::NotSettable => ValueTag::NotSettable,
+            IppValueRef::DeleteAttribute => ValueTag::DeleteAttribute,
+            IppValueRef::AdminDefine => ValueTag::AdminDefine,
+            IppValueRef::Extension(_, _) => ValueTag::Extension,
+            IppValueRef::Other(tag, _) => return tag,
+        };
+        tag as u8
+    }
+
+    /// Convert into owned value
+    pub fn to_owned(&self) -> IppValue {
+        match *self {
+            IppValueRef::Integer(i) => IppValue::Integer(i),
+            IppValueRef::Enum(i) => IppValue::Enum(i),
+            IppValueRef::OctetString(data) => IppValue::OctetString(data.to_vec()),
+            IppValueRef::TextWithoutLanguage(ref s) => IppValue::TextWithoutLanguage(s.to_string()),
+            IppValueRef::NameWithoutLanguage(ref s) => IppValue::NameWithoutLanguage(s.to_string()),
+            IppValueRef::TextWithLanguage { ref language, ref text } =>
+                IppValue::TextWithLanguage { language: language.to_string(), text: text.to_string() },
+            IppValueRef::NameWithLanguage { ref language, ref name } =>
+                IppValue::NameWithLanguage { language: language.to_string(), name: name.to_string() },
+            IppValueRef::Charset(ref s) => IppValue::Charset(s.to_string()),
+            IppValueRef::NaturalLanguage(ref s) => IppValue::NaturalLanguage(s.to_string()),
+            IppValueRef::Uri(ref s) => IppValue::Uri(s.to_string()),
+            IppValueRef::RangeOfInteger(min, max) => IppValue::RangeOfInteger(min, max),
+            IppValueRef::Boolean(b) => IppValue::Boolean(b),
+            IppValueRef::Keyword(ref s) => IppValue::Keyword(s.to_string()),
+            IppValueRef::ListOf(ref list) => IppValue::ListOf(list.iter().map(IppValueRef::to_owned).collect()),
+            IppValueRef::Collection(ref members) =>
+                IppValue::Collection(members.iter().map(|(name, value)| (name.to_string(), value.to_owned())).collect()),
+            IppValueRef::MimeMediaType(ref s) => IppValue::MimeMediaType(s.to_string()),
+            IppValueRef::DateTime(dt) => IppValue::DateTime(dt),
+            IppValueRef::MemberAttrName(ref s) => IppValue::MemberAttrName(s.to_string()),
+            IppValueRef::Resolution(crossfeed, feed, units) => IppValue::Resolution(crossfeed, feed, units),
+            IppValueRef::UriScheme(ref s) => IppValue::UriScheme(s.to_string()),
+            IppValueRef::Unsupported => IppValue::Unsupported,
+            IppValueRef::Unknown => IppValue::Unknown,
+            IppValueRef::NoValue => IppValue::NoValue,
+            IppValueRef::NotSettable => IppValue::NotSettable,
+            IppValueRef::DeleteAttribute => IppValue::DeleteAttribute,
+            IppValueRef::AdminDefine => IppValue::AdminDefine,
+            IppValueRef::Extension(tag, data) => IppValue::Extension(tag, data.to_vec()),
+            IppValueRef::Other(tag, data) => IppValue::Other(tag, data.to_vec()),
+        }
+    }
+}
+
+/// IPP attribute borrowing from the message buffer
+#[derive(Clone, Debug)]
+pub struct IppAttributeRef<'a> {
+    name: Cow<'a, str>,
+    value: IppValueRef<'a>
+}
+
+impl<'a> IppAttributeRef<'a> {
+    /// Return attribute name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return attribute value
+    pub fn value(&self) -> &IppValueRef<'a> {
+        &self.value
+    }
+
+    /// Convert into owned attribute
+    pub fn to_owned(&self) -> IppAttribute {
+        IppAttribute::new(&self.name, self.value.to_owned())
+    }
+}
+
+/// Attribute group borrowing from the message buffer
+#[derive(Clone, Debug)]
+pub struct IppAttributeGroupRef<'a> {
+    tag: DelimiterTag,
+    attributes: Vec<IppAttributeRef<'a>>
+}
+
+impl<'a> IppAttributeGroupRef<'a> {
+    /// Return group delimiter tag
+    pub fn tag(&self) -> DelimiterTag {
+        self.tag
+    }
+
+    /// Return attributes in wire order
+    pub fn attributes(&self) -> &[IppAttributeRef<'a>] {
+        &self.attributes
+    }
+
+    /// Find attribute by name, the first one if the name occurs more than once
+    pub fn get(&self, name: &str) -> Option<&IppAttributeRef<'a>> {
+        self.attributes.iter().find(|attr| attr.name() == name)
+    }
+
+    /// Convert into owned attribute group
+    pub fn to_owned(&self) -> IppAttributeGroup {
+        let mut retval = IppAttributeGroup::new(self.tag);
+        for attr in &self.attributes {
+            retval.push(attr.to_owned());
+        }
+        retval
+    }
+}
+
+/// IPP message borrowing from the buffer it was decoded from
+#[derive(Clone, Debug)]
+pub struct IppMessageRef<'a> {
+    header: IppHeader,
+    groups: Vec<IppAttributeGroupRef<'a>>,
+    data_offset: usize
+}
+
+impl<'a> IppMessageRef<'a> {
+    /// Decode message from the buffer with the default resource limits.
+    /// Deviations are handled like `ParseMode::Normal` does.
+    pub fn parse(data: &'a [u8]) -> Result<IppMessageRef<'a>> {
+        IppMessageRef::parse_with_limits(data, ParseLimits::default())
+    }
+
+    /// Decode message from the buffer with the given resource limits
+    pub fn parse_with_limits(data: &'a [u8], limits: ParseLimits) -> Result<IppMessageRef<'a>> {
+        let mut state = ParseState::<IppValueRef<'a>>::new(ParseMode::Normal, limits);
+        let header = match data.get(..8) {
+            Some(mut header) => IppHeader::from_reader(&mut header)?,
+            None => return Err(state.error(ParseErrorKind::UnexpectedEof))
+        };
+        debug!("IPP reply header: {:?}", header);
+
+        let mut pos = 8;
+        loop {
+            match state.slice_tag(pos, &data[pos..])? {
+                Some((size, end)) => {
+                    pos += size;
+                    if end {
+                        break;
+                    }
+                }
+                None if pos == data.len() => {
+                    state.start_tag(pos, None);
+                    return Err(state.error(ParseErrorKind::MissingEndOfAttributes));
+                }
+                None => return Err(state.error(ParseErrorKind::UnexpectedEof))
+            }
+        }
+
+        Ok(IppMessageRef { header, groups: state.into_groups(), data_offset: pos })
+    }
+
+    /// Return message header
+    pub fn header(&self) -> &IppHeader {
+        &self.header
+    }
+
+    /// Return attribute groups in wire order
+    pub fn groups(&self) -> &[IppAttributeGroupRef<'a>] {
+        &self.groups
+    }
+
+    /// Find attribute in the first group with the given tag which has it
+    pub fn get(&self, group: DelimiterTag, name: &str) -> Option<&IppAttributeRef<'a>> {
+        self.groups.iter().filter(|g| g.tag == group).filter_map(|g| g.get(name)).next()
+    }
+
+    /// Offset of the document data which follows the end of attributes
+    pub fn data_offset(&self) -> usize {
+        self.data_offset
+    }
+
+    /// Convert into owned attribute list
+    pub fn to_owned(&self) -> IppAttributeList {
+        let mut retval = IppAttributeList::new();
+        for group in &self.groups {
+            retval.add_group(group.to_owned());
+        }
+        retval
+    }
+}
+
+impl<'a> StateValue for IppValueRef<'a> {
+    type Name = Cow<'a, str>;
+    type Group = IppAttributeGroupRef<'a>;
+
+    fn list(values: Vec<IppValueRef<'a>>) -> IppValueRef<'a> {
+        IppValueRef::ListOf(values)
+    }
+
+    fn collection(members: Vec<(Cow<'a, str>, IppValueRef<'a>)>) -> IppValueRef<'a> {
+        IppValueRef::Collection(members)
+    }
+
+    fn tag(&self) -> u8 {
+        self.to_tag()
+    }
+
+    fn member_name(self) -> Option<Cow<'a, str>> {
+        match self {
+            IppValueRef::MemberAttrName(name) => Some(name),
+            _ => None
+        }
+    }
+
+    fn charset(&self) -> Option<&str> {
+        match *self {
+            IppValueRef::Charset(ref charset) => Some(charset),
+            _ => None
+        }
+    }
+
+    fn keyword(&self) -> Option<&str> {
+        match *self {
+            IppValueRef::Keyword(ref keyword) => Some(keyword),
+            _ => None
+        }
+    }
+
+    fn new_group(tag: DelimiterTag) -> IppAttributeGroupRef<'a> {
+        IppAttributeGroupRef { tag, attributes: Vec::new() }
+    }
+
+    fn group_tag(group: &IppAttributeGroupRef<'a>) -> DelimiterTag {
+        group.tag
+    }
+
+    fn group_contains(group: &IppAttributeGroupRef<'a>, name: &str) -> bool {
+        group.get(name).is_some()
+    }
+
+    fn group_add(group: &mut IppAttributeGroupRef<'a>, name: Cow<'a, str>, value: IppValueRef<'a>) {
+        group.attributes.push(IppAttributeRef { name, value });
+    }
+}
+
+impl<'a> DecodeValue<'a> for IppValueRef<'a> {
+    fn decode_name(data: &'a [u8]) -> Cow<'a, str> {
+        String::from_utf8_lossy(data)
+    }
+
+    fn decode(tag: u8, data: &'a [u8], charset: IppCharset) -> Result<IppValueRef<'a>> {
+        IppValueRef::from_bytes(tag, data, charset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use consts::attribute::ATTRIBUTES_CHARSET;
+    use parser::{IppParser, ParseError};
+
+    fn value(tag: ValueTag, name: &str, data: &[u8]) -> Vec<u8> {
+        let mut retval = vec![tag as u8];
+        retval.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        retval.extend_from_slice(name.as_bytes());
+        retval.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        retval.extend_from_slice(data);
+        retval
+    }
+
+    fn message(tags: &[Vec<u8>]) -> Vec<u8> {
+        let mut retval = vec![1, 1, 0, 2, 0, 0, 0, 1, DelimiterTag::OperationAttributes as u8];
+        retval.extend(value(ValueTag::Charset, ATTRIBUTES_CHARSET, b"utf-8"));
+        for tag in tags {
+            retval.extend_from_slice(tag);
+        }
+        retval
+    }
+
+    fn parse_error(data: &[u8], limits: ParseLimits) -> ParseError {
+        match IppMessageRef::parse_with_limits(data, limits) {
+            Err(IppError::ParseError(e)) => e,
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("message was accepted")
+        }
+    }
+
+    #[test]
+    fn test_same_as_owned() {
+        let mut data = message(&[
+            value(ValueTag::Keyword, "requested-attributes", b"all"),
+            value(ValueTag::Keyword, "", b"media-col"),
+            vec![DelimiterTag::JobAttributes as u8],
+            value(ValueTag::BegCollection, "media-col", b""),
+            value(ValueTag::MemberAttrName, "", b"media-type"),
+            value(ValueTag::Keyword, "", b"stationery"),
+            value(ValueTag::EndCollection, "", b""),
+            value(ValueTag::TextWithLanguage, "job-name", &[0, 2, b'e', b'n', 0, 1, b'x']),
+            vec![DelimiterTag::EndOfAttributes as u8]]);
+        let data_offset = data.len();
+        data.extend_from_slice(b"%PDF");
+
+        let message = IppMessageRef::parse(&data).unwrap();
+        assert_eq!(message.data_offset(), data_offset);
+        assert!(matches!(message.get(DelimiterTag::JobAttributes, "media-col").unwrap().value(),
+                         IppValueRef::Collection(_)));
+
+        let mut reader = &data[..];
+        let owned = IppParser::new(&mut reader).parse().unwrap();
+        let (mut borrowed, mut expected) = (Vec::new(), Vec::new());
+        message.to_owned().write(&mut borrowed).unwrap();
+        owned.attributes().write(&mut expected).unwrap();
+        assert_eq!(borrowed, expected);
+    }
+
+    #[test]
+    fn test_values_borrow_from_buffer() {
+        let data = message(&[
+            value(ValueTag::Keyword, "media", b"iso_a4_210x297mm"),
+            value(ValueTag::OctectStringUnspecified, "job-password", &[0xff, 0x00, 0xfe]),
+            value(ValueTag::BegCollection, "media-col", b""),
+            value(ValueTag::MemberAttrName, "", b"media-type"),
+            value(ValueTag::Keyword, "", b"stationery"),
+            value(ValueTag::EndCollection, "", b""),
+            vec![DelimiterTag::EndOfAttributes as u8]]);
+        let message = IppMessageRef::parse(&data).unwrap();
+
+        let in_buffer = |s: &[u8]| data.as_ptr_range().contains(&s.as_ptr());
+        let borrowed = |s: &Cow<str>| matches!(*s, Cow::Borrowed(s) if in_buffer(s.as_bytes()));
+        for attr in message.groups()[0].attributes() {
+            assert!(borrowed(&attr.name), "name {} is copied", attr.name());
+        }
+        let get = |name| message.get(DelimiterTag::OperationAttributes, name).unwrap().value();
+        assert!(matches!(*get("media"), IppValueRef::Keyword(ref s) if borrowed(s)));
+        assert!(matches!(*get("job-password"), IppValueRef::OctetString(s) if in_buffer(s)));
+        match *get("media-col") {
+            IppValueRef::Collection(ref members) => {
+                assert!(borrowed(&members[0].0));
+                assert!(matches!(members[0].1, IppValueRef::Keyword(ref s) if borrowed(s)));
+            }
+            ref value => panic!("unexpected value {:?}", value)
+        }
+    }
+
+    #[test]
+    fn test_deep_nesting_is_rejected() {
+        let mut tags = vec![value(ValueTag::BegCollection, "media-col", b"")];
+        for _ in 0..100_000 {
+            tags.push(value(ValueTag::MemberAttrName, "", b"m"));
+            tags.push(value(ValueTag::BegCollection, "", b""));
+        }
+        let error = parse_error(&message(&tags), ParseLimits::default());
+        assert_eq!(error.kind, ParseErrorKind::NestingTooDeep { limit: ParseLimits::default().max_depth });
+        assert_eq!(error.attribute.as_deref(), Some("media-col"));
+    }
+
+    #[test]
+    fn test_limits() {
+        let data = message(&[
+            value(ValueTag::Keyword, "requested-attributes", b"all"),
+            value(ValueTag::Keyword, "", b"media-col"),
+            vec![DelimiterTag::EndOfAttributes as u8]]);
+        let limits = ParseLimits { max_values: 1, ..ParseLimits::default() };
+        assert_eq!(parse_error(&data, limits).kind, ParseErrorKind::TooManyValues { limit: 1 });
+        let limits = ParseLimits { max_attributes: 1, ..ParseLimits::default() };
+        assert_eq!(parse_error(&data, limits).kind, ParseErrorKind::TooManyAttributes { limit: 1 });
+        let limits = ParseLimits { max_message_size: 20, ..ParseLimits::default() };
+        assert_eq!(parse_error(&data, limits).kind, ParseErrorKind::MessageTooLarge { limit: 20 });
+    }
+
+    #[test]
+    fn test_error_context() {
+        let data = message(&[vec![0x00]]);
+        let error = parse_error(&data, ParseLimits::default());
+        assert_eq!(error.kind, ParseErrorKind::InvalidTag(0x00));
+        assert_eq!((error.offset, error.group, error.attribute, error.tag),
+                   (data.len() - 1, Some(DelimiterTag::OperationAttributes), None, Some(0x00)));
+
+        let data = message(&[value(ValueTag::Keyword, "media", b"a4")]);
+        let error = parse_error(&data[..data.len() - 1], ParseLimits::default());
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedEof);
+        assert_eq!(error.attribute.as_deref(), Some("media"));
+
+        let error = parse_error(&data, ParseLimits::default());
+        assert_eq!(error.kind, ParseErrorKind::MissingEndOfAttributes);
+        assert_eq!((error.offset, error.tag), (data.len(), None));
+
+        let error = parse_error(&data[..5], ParseLimits::default());
+        assert_eq!((error.kind, error.offset, error.tag), (ParseErrorKind::UnexpectedEof, 0, None));
+    }
+}
@@ -0,0 +1,123 @@
+//!
+//! Character sets of text and name values
+//!
+
+/// Charsets which can be named by `attributes-charset`.
+/// RFC 8011 requires utf-8, legacy devices also use us-ascii and iso-8859-1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum IppCharset {
+    #[default]
+    Utf8,
+    UsAscii,
+    Iso8859_1,
+}
+
+impl IppCharset {
+    /// Find charset by its IANA name, case-insensitive
+    pub fn from_name(name: &str) -> Option<IppCharset> {
+        match name.to_ascii_lowercase().as_str() {
+            "utf-8" => Some(IppCharset::Utf8),
+            "us-ascii" => Some(IppCharset::UsAscii),
+            "iso-8859-1" => Some(IppCharset::Iso8859_1),
+            _ => None
+        }
+    }
+
+    /// IANA name as used in `attributes-charset`
+    pub fn name(self) -> &'static str {
+        match self {
+            IppCharset::Utf8 => "utf-8",
+            IppCharset::UsAscii => "us-ascii",
+            IppCharset::Iso8859_1 => "iso-8859-1",
+        }
+    }
+
+    /// Decode string, invalid bytes are replaced with U+FFFD
+    pub fn decode(self, data: &[u8]) -> String {
+        match self {
+            IppCharset::Utf8 => String::from_utf8_lossy(data).into_owned(),
+            IppCharset::UsAscii => data.iter()
+                .map(|&b| if b.is_ascii() { char::from(b) } else { char::REPLACEMENT_CHARACTER })
+                .collect(),
+            // iso-8859-1 maps one to one onto the first 256 code points
+            IppCharset::Iso8859_1 => data.iter().map(|&b| char::from(b)).collect(),
+        }
+    }
+
+    /// Encode string, characters which the charset cannot represent are replaced with '?'
+    /// The result is never longer than the UTF-8 encoding, which `IppValue::check_encodable` relies on.
+    pub fn encode(self, s: &str) -> Vec<u8> {
+        let limit = match self {
+            IppCharset::Utf8 => return s.as_bytes().to_vec(),
+            IppCharset::UsAscii => 0x80,
+            IppCharset::Iso8859_1 => 0x100,
+        };
+        s.chars().map(|c| if (c as u32) < limit { c as u8 } else { b'?' }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::*;
+    use attribute::IppAttribute;
+    use consts::tag::DelimiterTag;
+    use parser::IppParser;
+    use value::IppValue;
+    use IppHeader;
+
+    // operation group with attributes-charset iso-8859-1 and a job-name "caf\xe9"
+    fn latin1_request() -> Vec<u8> {
+        let mut data = Vec::new();
+        IppHeader::new(0x0101, 0x0002, 1).write(&mut data).unwrap();
+        data.push(DelimiterTag::OperationAttributes as u8);
+        IppAttribute::new("attributes-charset", IppValue::Charset("iso-8859-1".to_string()))
+            .write(&mut data).unwrap();
+        data.extend_from_slice(&[0x42, 0x00, 0x08]);
+        data.extend_from_slice(b"job-name");
+        data.extend_from_slice(&[0x00, 0x04, b'c', b'a', b'f', 0xe9]);
+        data.push(DelimiterTag::EndOfAttributes as u8);
+        data
+    }
+
+    #[test]
+    fn test_charset_names() {
+        assert_eq!(IppCharset::from_name("ISO-8859-1"), Some(IppCharset::Iso8859_1));
+        assert_eq!(IppCharset::from_name("utf-16"), None);
+        assert_eq!(IppCharset::UsAscii.name(), "us-ascii");
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        assert_eq!(IppCharset::Iso8859_1.decode(b"caf\xe9"), "caf\u{e9}");
+        assert_eq!(IppCharset::UsAscii.decode(b"caf\xe9"), "caf\u{fffd}");
+        assert_eq!(IppCharset::Iso8859_1.encode("caf\u{e9}\u{20ac}"), b"caf\xe9?");
+        assert_eq!(IppCharset::UsAscii.encode("caf\u{e9}"), b"caf?");
+        assert_eq!(IppCharset::Utf8.encode("caf\u{e9}"), "caf\u{e9}".as_bytes());
+    }
+
+    #[test]
+    fn test_latin1_request() {
+        let data = latin1_request();
+        let mut cursor = Cursor::new(&data);
+        let attributes = IppParser::new(&mut cursor).parse().unwrap().into_parts().1;
+
+        assert_eq!(attributes.charset(), IppCharset::Iso8859_1);
+        let job_name = attributes.get(DelimiterTag::OperationAttributes, "job-name").unwrap();
+        match *job_name.value() {
+            IppValue::NameWithoutLanguage(ref name) => assert_eq!(name, "caf\u{e9}"),
+            ref value => panic!("unexpected value {:?}", value)
+        }
+
+        // the attribute alone is encoded with the given charset, the list with its own
+        let mut encoded = Vec::new();
+        job_name.write_with_charset(&mut encoded, IppCharset::Iso8859_1).unwrap();
+        assert!(data.windows(encoded.len()).any(|w| w == &encoded[..]));
+
+        let mut written = Vec::new();
+        IppHeader::new(0x0101, 0x0002, 1).write(&mut written).unwrap();
+        attributes.write(&mut written).unwrap();
+        assert_eq!(written, data);
+    }
+
+}
@@ -6,34 +6,64 @@ use enum_primitive::FromPrimitive;
 
 use hyper::client::request::Request;
 use hyper::method::Method;
-use hyper::Url;
 use hyper::status::StatusCode;
 
 use ::{IppError, Result};
-use request::{IppRequestResponse,IppRequestTrait};
+use request::{IppRequestResponse,IppRequestTrait,IppOwnedRequest};
 use operation::IppOperation;
 use attribute::IppAttributeList;
-use parser::IppParser;
+use parser::{IppParser, ParseLimits};
+use charset::IppCharset;
+use target;
 use consts::statuscode;
 
 /// IPP client.
 ///
 /// IPP client is responsible for sending requests to IPP server.
 pub struct IppClient {
-    uri: String
+    uri: String,
+    charset: Option<IppCharset>,
+    natural_language: Option<String>,
+    parse_limits: ParseLimits,
+    check_limits: bool
 }
 
 impl IppClient {
     /// Create new instance of the client
     pub fn new(uri: &str) -> IppClient {
         IppClient {
-            uri: uri.to_string()
+            uri: uri.to_string(),
+            charset: None,
+            natural_language: None,
+            parse_limits: ParseLimits::unlimited(),
+            check_limits: false
         }
     }
 
+    /// Set charset of the requests, utf-8 by default
+    pub fn set_charset(&mut self, charset: IppCharset) {
+        self.charset = Some(charset);
+    }
+
+    /// Set natural language of the requests, "en" by default
+    pub fn set_natural_language(&mut self, language: &str) {
+        self.natural_language = Some(language.to_string());
+    }
+
+    /// Set resource limits of the responses, `ParseLimits::unlimited()` by default
+    pub fn set_parse_limits(&mut self, limits: ParseLimits) {
+        self.parse_limits = limits;
+    }
+
+    /// Reject requests with attributes which exceed the RFC 8011 maximum lengths
+    /// before anything is sent, disabled by default
+    pub fn set_check_limits(&mut self, check_limits: bool) {
+        self.check_limits = check_limits;
+    }
+
     /// send IPP operation
     pub fn send<T: IppOperation>(&self, mut operation: T) -> Result<IppAttributeList> {
-        match self.send_request(&mut operation.to_ipp_request(&self.uri)) {
+        match self.send_request(&mut operation.to_ipp_request(&self.uri)?) {
             Ok(resp) => {
                 if resp.header().operation_status > 3 {
                     // IPP error
@@ -48,17 +78,28 @@ impl IppClient {
         }
     }
 
+    /// Send owned request and return response. The document is rewound first,
+    /// so a request which failed can be sent again.
+    pub fn send_owned<'b>(&self, request: &mut IppOwnedRequest) -> Result<IppRequestResponse<'b>> {
+        self.send_request(&mut request.to_request_response()?)
+    }
+
     /// Send request and return response
     pub fn send_request<'a, 'b>(&self, request: &'a mut IppRequestResponse<'a>) -> Result<IppRequestResponse<'b>> {
-        match Url::parse(&self.uri) {
-            Ok(mut url) => {
-                if url.scheme() == "ipp" {
-                    url.set_scheme("http").map_err(|_| IppError::RequestError("Invalid URI".to_string()))?;;
-                    if  url.port().is_none() {
-                        url.set_port(Some(631)).map_err(|_| IppError::RequestError("Invalid URI".to_string()))?;
-                    }
-                }
+        if let Some(charset) = self.charset {
+            request.set_charset(charset);
+        }
+        if let Some(ref language) = self.natural_language {
+            request.set_natural_language(language);
+        }
+        if self.check_limits {
+            request.set_check_limits(true);
+        }
+        // a request which cannot be written fails before connecting
+        request.check()?;
 
+        match target::http_url(&self.uri) {
+            Ok(url) => {
                 debug!("Request URI: {}", url);
 
                 // create request and set headers
@@ -79,9 +120,10 @@ impl IppClient {
                     // HTTP 200 assumes we have IPP response to parse
                     let mut reader = BufReader::new(http_resp);
                     let mut parser = IppParser::new(&mut reader);
-                    let resp = IppRequestResponse::from_parser(&mut parser)?;
+                    parser.set_limits(self.parse_limits);
+                    let (header, attributes) = parser.parse()?.into_parts();
 
-                    Ok(resp)
+                    Ok(IppRequestResponse::from_parts(header, attributes))
                 } else {
                     error!("HTTP error: {}", http_resp.status);
                     Err(IppError::RequestError(
@@ -94,8 +136,32 @@ impl IppClient {
             }
             Err(err) => {
                 error!("Invalid URI: {}", self.uri);
-                Err(IppError::RequestError(err.to_string()))
+                Err(err)
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use attribute::IppAttribute;
+    use value::IppValue;
+    use consts::operation::Operation;
+    use consts::tag::DelimiterTag;
+
+    #[test]
+    fn test_invalid_request_is_not_sent() {
+        // nothing listens on the discard port, a connection attempt would fail with another error
+        let mut client = IppClient::new("ipp://127.0.0.1:9/printer");
+        client.set_check_limits(true);
+        let mut request = IppRequestResponse::new(Operation::PrintJob, "ipp://127.0.0.1:9/printer");
+        request.set_attribute(DelimiterTag::JobAttributes,
+                              IppAttribute::new("job-name", IppValue::NameWithoutLanguage("x".repeat(256))));
+        match client.send_request(&mut request) {
+            Err(IppError::LengthError { ref attribute, length: 256, limit: 255 }) => assert_eq!(attribute, "job-name"),
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("request was sent")
+        }
+    }
+}
@@ -9,6 +9,17 @@ pub enum DelimiterTag {
     EndOfAttributes = 0x03,
     PrinterAttributes = 0x04,
     UnsupportedAttributes = 0x05,
+    SubscriptionAttributes = 0x06,
+    EventNotificationAttributes = 0x07,
+    ResourceAttributes = 0x08,
+    DocumentAttributes = 0x09,
+    SystemAttributes = 0x0a,
+    // reserved for future delimiters, groups with these tags are kept as is
+    Reserved0B = 0x0b,
+    Reserved0C = 0x0c,
+    Reserved0D = 0x0d,
+    Reserved0E = 0x0e,
+    Reserved0F = 0x0f,
 }
 }
 
@@ -18,6 +29,9 @@ pub enum ValueTag {
     Unsupported = 0x10,
     Unknown = 0x12,
     NoValue = 0x13,
+    NotSettable = 0x15,
+    DeleteAttribute = 0x16,
+    AdminDefine = 0x17,
     Integer = 0x21,
     Boolean = 0x22,
     Enum = 0x23,
@@ -38,13 +52,82 @@ pub enum ValueTag {
     NaturalLanguage = 0x48,
     MimeMediaType = 0x49,
     MemberAttrName = 0x4a,
+    Extension = 0x7f,
 }
 }
 
+const GROUP_NAMES: [(DelimiterTag, &str); 10] = [
+    (DelimiterTag::OperationAttributes, "operation-attributes-tag"),
+    (DelimiterTag::JobAttributes, "job-attributes-tag"),
+    (DelimiterTag::EndOfAttributes, "end-of-attributes-tag"),
+    (DelimiterTag::PrinterAttributes, "printer-attributes-tag"),
+    (DelimiterTag::UnsupportedAttributes, "unsupported-attributes-tag"),
+    (DelimiterTag::SubscriptionAttributes, "subscription-attributes-tag"),
+    (DelimiterTag::EventNotificationAttributes, "event-notification-attributes-tag"),
+    (DelimiterTag::ResourceAttributes, "resource-attributes-tag"),
+    (DelimiterTag::DocumentAttributes, "document-attributes-tag"),
+    (DelimiterTag::SystemAttributes, "system-attributes-tag"),
+];
+
+impl DelimiterTag {
+    /// Tag name as used by RFC 8010, for example `job-attributes-tag`. Reserved tags have no name.
+    pub fn name(self) -> Option<&'static str> {
+        GROUP_NAMES.iter().find(|&&(tag, _)| tag == self).map(|&(_, name)| name)
+    }
+
+    /// Find delimiter tag by its name
+    pub fn from_name(name: &str) -> Option<DelimiterTag> {
+        GROUP_NAMES.iter().find(|&&(_, n)| n == name).map(|&(tag, _)| tag)
+    }
+}
+
+const VALUE_TAG_NAMES: [(ValueTag, &str); 27] = [
+    (ValueTag::Unsupported, "unsupported"),
+    (ValueTag::Unknown, "unknown"),
+    (ValueTag::NoValue, "no-value"),
+    (ValueTag::NotSettable, "not-settable"),
+    (ValueTag::DeleteAttribute, "delete-attribute"),
+    (ValueTag::AdminDefine, "admin-define"),
+    (ValueTag::Integer, "integer"),
+    (ValueTag::Boolean, "boolean"),
+    (ValueTag::Enum, "enum"),
+    (ValueTag::OctectStringUnspecified, "octetString"),
+    (ValueTag::DateTime, "dateTime"),
+    (ValueTag::Resolution, "resolution"),
+    (ValueTag::RangeOfInteger, "rangeOfInteger"),
+    (ValueTag::BegCollection, "collection"),
+    (ValueTag::TextWithLanguage, "textWithLanguage"),
+    (ValueTag::NameWithLanguage, "nameWithLanguage"),
+    (ValueTag::EndCollection, "endCollection"),
+    (ValueTag::TextWithoutLanguage, "textWithoutLanguage"),
+    (ValueTag::NameWithoutLanguage, "nameWithoutLanguage"),
+    (ValueTag::Keyword, "keyword"),
+    (ValueTag::Uri, "uri"),
+    (ValueTag::UriScheme, "uriScheme"),
+    (ValueTag::Charset, "charset"),
+    (ValueTag::NaturalLanguage, "naturalLanguage"),
+    (ValueTag::MimeMediaType, "mimeMediaType"),
+    (ValueTag::MemberAttrName, "memberAttrName"),
+    (ValueTag::Extension, "extension"),
+];
+
+impl ValueTag {
+    /// Syntax name as used by RFC 8011 and ipptool, for example `rangeOfInteger`
+    pub fn name(self) -> &'static str {
+        VALUE_TAG_NAMES.iter().find(|&&(tag, _)| tag == self).map_or("unknown", |&(_, name)| name)
+    }
+
+    /// Find value tag by its syntax name
+    pub fn from_name(name: &str) -> Option<ValueTag> {
+        VALUE_TAG_NAMES.iter().find(|&&(_, n)| n == name).map(|&(tag, _)| tag)
+    }
+}
+
+/// Value tags occupy the whole 0x10..0xff range, unknown ones are kept as opaque values
 pub fn is_value_tag(value: u8) -> bool {
-    value >= 0x10 && value <= 0x4a
+    value >= 0x10
 }
 
 pub fn is_delimiter_tag(value: u8) -> bool {
-    value >= 0x01 && value <= 0x05
+    (0x01..=0x0f).contains(&value)
 }
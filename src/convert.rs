@@ -0,0 +1,285 @@
+//!
+//! Conversions between IppValue and Rust types
+//!
+use std::convert::TryFrom;
+use std::ops::RangeInclusive;
+use std::time::SystemTime;
+
+use ::{Result, IppError};
+use attribute::IppAttribute;
+use datetime::IppDateTime;
+use value::IppValue;
+
+fn mismatch(expected: &str, value: &IppValue) -> IppError {
+    IppError::ValueError(format!("Expected {}, found {} value '{}'", expected, value.syntax(), value))
+}
+
+impl<'a> TryFrom<&'a IppValue> for i32 {
+    type Error = IppError;
+
+    /// Extract integer or enum value
+    fn try_from(value: &'a IppValue) -> Result<i32> {
+        match *value {
+            IppValue::Integer(i) | IppValue::Enum(i) => Ok(i),
+            _ => Err(mismatch("integer", value))
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a IppValue> for bool {
+    type Error = IppError;
+
+    fn try_from(value: &'a IppValue) -> Result<bool> {
+        match *value {
+            IppValue::Boolean(b) => Ok(b),
+            _ => Err(mismatch("boolean", value))
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a IppValue> for &'a str {
+    type Error = IppError;
+
+    /// Extract any string syntax. Language-tagged values return the string without the language.
+    fn try_from(value: &'a IppValue) -> Result<&'a str> {
+        match *value {
+            IppValue::Keyword(ref s) |
+            IppValue::TextWithoutLanguage(ref s) | IppValue::NameWithoutLanguage(ref s) |
+            IppValue::TextWithLanguage { text: ref s, .. } | IppValue::NameWithLanguage { name: ref s, .. } |
+            IppValue::Charset(ref s) | IppValue::NaturalLanguage(ref s) |
+            IppValue::Uri(ref s) | IppValue::UriScheme(ref s) | IppValue::MimeMediaType(ref s) |
+            IppValue::MemberAttrName(ref s) => Ok(s),
+            _ => Err(mismatch("string", value))
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a IppValue> for String {
+    type Error = IppError;
+
+    fn try_from(value: &'a IppValue) -> Result<String> {
+        <&str>::try_from(value).map(|s| s.to_string())
+    }
+}
+
+impl<'a> TryFrom<&'a IppValue> for RangeInclusive<i32> {
+    type Error = IppError;
+
+    fn try_from(value: &'a IppValue) -> Result<RangeInclusive<i32>> {
+        match *value {
+            IppValue::RangeOfInteger(min, max) => Ok(min..=max),
+            _ => Err(mismatch("rangeOfInteger", value))
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a IppValue> for (i32, i32, i8) {
+    type Error = IppError;
+
+    /// Extract resolution as (cross feed, feed, units)
+    fn try_from(value: &'a IppValue) -> Result<(i32, i32, i8)> {
+        match *value {
+            IppValue::Resolution(crossfeed, feed, units) => Ok((crossfeed, feed, units)),
+            _ => Err(mismatch("resolution", value))
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a IppValue> for IppDateTime {
+    type Error = IppError;
+
+    fn try_from(value: &'a IppValue) -> Result<IppDateTime> {
+        match *value {
+            IppValue::DateTime(dt) => Ok(dt),
+            _ => Err(mismatch("dateTime", value))
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a IppValue> for SystemTime {
+    type Error = IppError;
+
+    fn try_from(value: &'a IppValue) -> Result<SystemTime> {
+        IppDateTime::try_from(value).map(|dt| dt.to_system_time())
+    }
+}
+
+impl<'a, T> TryFrom<&'a IppValue> for Vec<T> where T: TryFrom<&'a IppValue, Error = IppError> {
+    type Error = IppError;
+
+    /// Extract all values of a 1setOf, a single value gives a one-element vector
+    fn try_from(value: &'a IppValue) -> Result<Vec<T>> {
+        value.into_iter().map(T::try_from).collect()
+    }
+}
+
+// conversions from attributes delegate to the value and name the attribute in the error
+macro_rules! attribute_try_from {
+    ($($t:ty),*) => {
+        $(
+        impl<'a> TryFrom<&'a IppAttribute> for $t {
+            type Error = IppError;
+
+            fn try_from(attr: &'a IppAttribute) -> Result<$t> {
+                <$t>::try_from(attr.value()).map_err(|e| match e {
+                    IppError::ValueError(msg) => IppError::ValueError(format!("{}: {}", attr.name(), msg)),
+                    e => e
+                })
+            }
+        }
+        )*
+    }
+}
+
+attribute_try_from!(i32, bool, &'a str, String, RangeInclusive<i32>, (i32, i32, i8), IppDateTime, SystemTime,
+                    Vec<i32>, Vec<bool>, Vec<&'a str>, Vec<String>, Vec<RangeInclusive<i32>>,
+                    Vec<(i32, i32, i8)>, Vec<IppDateTime>);
+
+impl From<i32> for IppValue {
+    fn from(i: i32) -> IppValue {
+        IppValue::Integer(i)
+    }
+}
+
+impl From<bool> for IppValue {
+    fn from(b: bool) -> IppValue {
+        IppValue::Boolean(b)
+    }
+}
+
+impl From<RangeInclusive<i32>> for IppValue {
+    fn from(range: RangeInclusive<i32>) -> IppValue {
+        IppValue::RangeOfInteger(*range.start(), *range.end())
+    }
+}
+
+impl From<(i32, i32, i8)> for IppValue {
+    /// Create resolution from (cross feed, feed, units)
+    fn from((crossfeed, feed, units): (i32, i32, i8)) -> IppValue {
+        IppValue::Resolution(crossfeed, feed, units)
+    }
+}
+
+impl From<IppDateTime> for IppValue {
+    fn from(dt: IppDateTime) -> IppValue {
+        IppValue::DateTime(dt)
+    }
+}
+
+impl<T> From<Vec<T>> for IppValue where T: Into<IppValue> {
+    /// Create 1setOf value
+    fn from(list: Vec<T>) -> IppValue {
+        IppValue::ListOf(list.into_iter().map(Into::into).collect())
+    }
+}
+
+impl IppValue {
+    /// Create keyword value
+    pub fn keyword(s: &str) -> IppValue {
+        IppValue::Keyword(s.to_string())
+    }
+
+    /// Create nameWithoutLanguage value
+    pub fn name(s: &str) -> IppValue {
+        IppValue::NameWithoutLanguage(s.to_string())
+    }
+
+    /// Create textWithoutLanguage value
+    pub fn text(s: &str) -> IppValue {
+        IppValue::TextWithoutLanguage(s.to_string())
+    }
+
+    /// Create uri value
+    pub fn uri(s: &str) -> IppValue {
+        IppValue::Uri(s.to_string())
+    }
+
+    /// Create 1setOf keyword value
+    pub fn keywords(list: &[&str]) -> IppValue {
+        IppValue::ListOf(list.iter().map(|s| IppValue::keyword(s)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn value_error<T: ::std::fmt::Debug>(result: Result<T>) -> String {
+        match result {
+            Err(IppError::ValueError(msg)) => msg,
+            other => panic!("expected value error, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_integer() {
+        assert_eq!(i32::try_from(&IppValue::Integer(-5)).unwrap(), -5);
+        assert_eq!(i32::try_from(&IppValue::Enum(9)).unwrap(), 9);
+        assert!(value_error(i32::try_from(&IppValue::keyword("none"))).starts_with("Expected integer, found keyword"));
+        assert!(matches!(IppValue::from(3), IppValue::Integer(3)));
+    }
+
+    #[test]
+    fn test_boolean() {
+        assert!(bool::try_from(&IppValue::Boolean(true)).unwrap());
+        assert!(value_error(bool::try_from(&IppValue::Integer(1))).starts_with("Expected boolean, found integer"));
+        assert!(matches!(IppValue::from(false), IppValue::Boolean(false)));
+    }
+
+    #[test]
+    fn test_string() {
+        let language = IppValue::TextWithLanguage { language: "en".to_string(), text: "hello".to_string() };
+        assert_eq!(<&str>::try_from(&language).unwrap(), "hello");
+        assert_eq!(<&str>::try_from(&IppValue::uri("ipp://host")).unwrap(), "ipp://host");
+        assert_eq!(String::try_from(&IppValue::name("job")).unwrap(), "job");
+        assert!(value_error(String::try_from(&IppValue::Integer(1))).starts_with("Expected string, found integer"));
+        assert!(value_error(<&str>::try_from(&IppValue::OctetString(b"raw".to_vec()))).starts_with("Expected string"));
+    }
+
+    #[test]
+    fn test_range_and_resolution() {
+        assert_eq!(RangeInclusive::<i32>::try_from(&IppValue::RangeOfInteger(1, 10)).unwrap(), 1..=10);
+        assert!(value_error(RangeInclusive::<i32>::try_from(&IppValue::Integer(1))).starts_with("Expected rangeOfInteger"));
+        assert!(matches!(IppValue::from(2..=4), IppValue::RangeOfInteger(2, 4)));
+
+        assert_eq!(<(i32, i32, i8)>::try_from(&IppValue::Resolution(600, 300, 3)).unwrap(), (600, 300, 3));
+        assert!(value_error(<(i32, i32, i8)>::try_from(&IppValue::RangeOfInteger(1, 2))).starts_with("Expected resolution"));
+        assert!(matches!(IppValue::from((600, 600, 3)), IppValue::Resolution(600, 600, 3)));
+    }
+
+    #[test]
+    fn test_date_time() {
+        let dt = IppDateTime::from_system_time(UNIX_EPOCH).unwrap();
+        assert_eq!(IppDateTime::try_from(&IppValue::from(dt)).unwrap(), dt);
+        assert_eq!(SystemTime::try_from(&IppValue::DateTime(dt)).unwrap(), UNIX_EPOCH);
+        assert!(value_error(IppDateTime::try_from(&IppValue::Integer(0))).starts_with("Expected dateTime"));
+        assert!(value_error(SystemTime::try_from(&IppValue::text("now"))).starts_with("Expected dateTime"));
+    }
+
+    #[test]
+    fn test_vec() {
+        let list = IppValue::from(vec![1, 2, 3]);
+        match list {
+            IppValue::ListOf(ref items) => assert_eq!(items.len(), 3),
+            ref value => panic!("unexpected value {:?}", value)
+        }
+        assert_eq!(Vec::<i32>::try_from(&list).unwrap(), [1, 2, 3]);
+        // a scalar is a 1setOf with one value
+        assert_eq!(Vec::<i32>::try_from(&IppValue::Enum(4)).unwrap(), [4]);
+        assert_eq!(Vec::<&str>::try_from(&IppValue::keywords(&["a", "b"])).unwrap(), ["a", "b"]);
+
+        let mixed = IppValue::ListOf(vec![IppValue::Integer(1), IppValue::keyword("two")]);
+        assert!(value_error(Vec::<i32>::try_from(&mixed)).starts_with("Expected integer, found keyword"));
+        assert!(value_error(Vec::<bool>::try_from(&IppValue::Integer(1))).starts_with("Expected boolean"));
+    }
+
+    #[test]
+    fn test_attribute() {
+        let attr = IppAttribute::new("copies", IppValue::Integer(2));
+        assert_eq!(i32::try_from(&attr).unwrap(), 2);
+        assert_eq!(Vec::<i32>::try_from(&attr).unwrap(), [2]);
+        assert!(value_error(String::try_from(&attr)).starts_with("copies: Expected string"));
+        assert!(value_error(Vec::<IppDateTime>::try_from(&attr)).starts_with("copies: Expected dateTime"));
+    }
+}
@@ -0,0 +1,428 @@
+//!
+//! IPP dateTime value (RFC 2579 DateAndTime)
+//!
+use std::io::Write;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use ::{Result, IppError};
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        2 => if is_leap_year(year) { 29 } else { 28 },
+        4 | 6 | 9 | 11 => 30,
+        _ => 31
+    }
+}
+
+// days since 1970-01-01 of the proleptic Gregorian date
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let m = i64::from(month);
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// inverse of days_from_civil
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let z = days + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn invalid(what: &str) -> IppError {
+    IppError::AttributeError(format!("Invalid dateTime: {}", what))
+}
+
+/// Date and time with UTC offset as carried by IPP dateTime values
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IppDateTime {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minutes: u8,
+    seconds: u8,
+    deciseconds: u8,
+    /// b'+' or b'-', kept separately so that "-00:00" survives re-encoding
+    utc_dir: u8,
+    utc_hours: u8,
+    utc_minutes: u8
+}
+
+impl IppDateTime {
+    /// Create date-time value, validating every field
+    ///
+    /// * `utc_offset` - offset from UTC in minutes, positive east of Greenwich<br/>
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(year: u16, month: u8, day: u8, hour: u8, minutes: u8, seconds: u8,
+               deciseconds: u8, utc_offset: i16) -> Result<IppDateTime> {
+        let offset = utc_offset.unsigned_abs();
+        let retval = IppDateTime {
+            year, month, day, hour, minutes, seconds, deciseconds,
+            utc_dir: if utc_offset < 0 { b'-' } else { b'+' },
+            utc_hours: (offset / 60).min(255) as u8,
+            utc_minutes: (offset % 60) as u8
+        };
+        retval.validate()?;
+        Ok(retval)
+    }
+
+    /// Decode date-time from the 11 bytes of the value field
+    pub fn from_bytes(data: &[u8]) -> Result<IppDateTime> {
+        if data.len() != 11 {
+            return Err(invalid("value length must be 11"));
+        }
+        let mut reader = data;
+        let retval = IppDateTime {
+            year: reader.read_u16::<BigEndian>()?,
+            month: data[2],
+            day: data[3],
+            hour: data[4],
+            minutes: data[5],
+            seconds: data[6],
+            deciseconds: data[7],
+            utc_dir: data[8],
+            utc_hours: data[9],
+            utc_minutes: data[10]
+        };
+        retval.validate()?;
+        Ok(retval)
+    }
+
+    /// Encode date-time into the 11 bytes of the value field
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize> {
+        writer.write_u16::<BigEndian>(self.year)?;
+        writer.write_all(&[self.month, self.day, self.hour, self.minutes, self.seconds,
+                           self.deciseconds, self.utc_dir, self.utc_hours, self.utc_minutes])?;
+        Ok(11)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !(1..=12).contains(&self.month) {
+            return Err(invalid("month out of range"));
+        }
+        if self.day < 1 || self.day > days_in_month(i64::from(self.year), self.month) {
+            return Err(invalid("day out of range"));
+        }
+        if self.hour > 23 || self.minutes > 59 || self.seconds > 60 || self.deciseconds > 9 {
+            return Err(invalid("time out of range"));
+        }
+        if (self.utc_dir != b'+' && self.utc_dir != b'-') || self.utc_hours > 14 || self.utc_minutes > 59 {
+            return Err(invalid("UTC offset out of range"));
+        }
+        Ok(())
+    }
+
+    /// Create UTC date-time from system time, truncated to deciseconds
+    pub fn from_system_time(time: SystemTime) -> Result<IppDateTime> {
+        IppDateTime::from_system_time_with_offset(time, 0)
+    }
+
+    /// Create date-time from system time expressed in the given UTC offset (in minutes)
+    pub fn from_system_time_with_offset(time: SystemTime, utc_offset: i16) -> Result<IppDateTime> {
+        // split into whole seconds and deciseconds relative to the epoch
+        let (secs, decis) = match time.duration_since(UNIX_EPOCH) {
+            Ok(d) => (d.as_secs() as i64, i64::from(d.subsec_millis() / 100)),
+            Err(e) => {
+                let d = e.duration();
+                let millis = i64::from(d.subsec_millis());
+                let secs = -(d.as_secs() as i64) - if millis > 0 { 1 } else { 0 };
+                (secs, if millis > 0 { (1000 - millis) / 100 } else { 0 })
+            }
+        };
+        let local = secs + i64::from(utc_offset) * 60;
+        let days = local.div_euclid(86_400);
+        let rem = local.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        if !(0..=i64::from(u16::MAX)).contains(&year) {
+            return Err(invalid("year out of range"));
+        }
+        IppDateTime::new(year as u16, month, day, (rem / 3600) as u8, (rem % 3600 / 60) as u8,
+                         (rem % 60) as u8, decis as u8, utc_offset)
+    }
+
+    /// Convert to system time
+    pub fn to_system_time(&self) -> SystemTime {
+        let days = days_from_civil(i64::from(self.year), self.month, self.day);
+        let secs = days * 86_400 + i64::from(self.hour) * 3600 + i64::from(self.minutes) * 60 +
+            i64::from(self.seconds) - i64::from(self.utc_offset()) * 60;
+        let millis = secs * 1000 + i64::from(self.deciseconds) * 100;
+        if millis >= 0 {
+            UNIX_EPOCH + Duration::from_millis(millis as u64)
+        } else {
+            UNIX_EPOCH - Duration::from_millis(millis.unsigned_abs())
+        }
+    }
+
+    /// Format as RFC 3339 string, for example `2024-03-01T12:30:00.5+01:00`
+    pub fn to_rfc3339(&self) -> String {
+        let fraction = if self.deciseconds > 0 { format!(".{}", self.deciseconds) } else { String::new() };
+        format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}{}{:02}:{:02}",
+                self.year, self.month, self.day, self.hour, self.minutes, self.seconds, fraction,
+                self.utc_dir as char, self.utc_hours, self.utc_minutes)
+    }
+
+    /// Parse RFC 3339 string. Fractions of a second are truncated to deciseconds.
+    pub fn parse_rfc3339(s: &str) -> Result<IppDateTime> {
+        fn number<T: FromStr>(s: &str, start: usize, len: usize) -> Result<T> {
+            s.get(start..start + len)
+                .filter(|n| n.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| invalid("malformed RFC 3339 string"))
+        }
+        fn expect(s: &str, pos: usize, chars: &[u8]) -> Result<()> {
+            match s.as_bytes().get(pos) {
+                Some(c) if chars.contains(c) => Ok(()),
+                _ => Err(invalid("malformed RFC 3339 string"))
+            }
+        }
+
+        expect(s, 4, b"-")?;
+        expect(s, 7, b"-")?;
+        expect(s, 10, b"Tt ")?;
+        expect(s, 13, b":")?;
+        expect(s, 16, b":")?;
+
+        let (year, month, day) = (number(s, 0, 4)?, number(s, 5, 2)?, number(s, 8, 2)?);
+        let (hour, minutes, seconds) = (number(s, 11, 2)?, number(s, 14, 2)?, number(s, 17, 2)?);
+
+        let mut pos = 19;
+        let mut deciseconds = 0;
+        if s.as_bytes().get(pos) == Some(&b'.') {
+            let digits = s[pos + 1..].bytes().take_while(|b| b.is_ascii_digit()).count();
+            if digits == 0 {
+                return Err(invalid("malformed RFC 3339 string"));
+            }
+            deciseconds = number(s, pos + 1, 1)?;
+            pos += 1 + digits;
+        }
+
+        let (utc_dir, utc_hours, utc_minutes) = match &s[pos..] {
+            "Z" | "z" => (b'+', 0, 0),
+            tz => {
+                if tz.len() != 6 {
+                    return Err(invalid("malformed RFC 3339 string"));
+                }
+                expect(tz, 0, b"+-")?;
+                expect(tz, 3, b":")?;
+                // the sign is kept as given, "-00:00" means the local offset is unknown
+                (tz.as_bytes()[0], number(tz, 1, 2)?, number(tz, 4, 2)?)
+            }
+        };
+
+        let retval = IppDateTime {
+            year, month, day, hour, minutes, seconds, deciseconds, utc_dir, utc_hours, utc_minutes
+        };
+        retval.validate()?;
+        Ok(retval)
+    }
+
+    /// Return year
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Return month, 1..12
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Return day of month, 1..31
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Return hour, 0..23
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// Return minutes, 0..59
+    pub fn minutes(&self) -> u8 {
+        self.minutes
+    }
+
+    /// Return seconds, 0..60
+    pub fn seconds(&self) -> u8 {
+        self.seconds
+    }
+
+    /// Return deciseconds, 0..9
+    pub fn deciseconds(&self) -> u8 {
+        self.deciseconds
+    }
+
+    /// Offset from UTC in minutes, positive east of Greenwich
+    pub fn utc_offset(&self) -> i16 {
+        let offset = i16::from(self.utc_hours) * 60 + i16::from(self.utc_minutes);
+        if self.utc_dir == b'-' { -offset } else { offset }
+    }
+}
+
+impl fmt::Display for IppDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_rfc3339())
+    }
+}
+
+impl FromStr for IppDateTime {
+    type Err = IppError;
+
+    fn from_str(s: &str) -> Result<IppDateTime> {
+        IppDateTime::parse_rfc3339(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use consts::tag::ValueTag;
+    use value::IppValue;
+
+    fn parse(s: &str) -> IppDateTime {
+        IppDateTime::parse_rfc3339(s).unwrap()
+    }
+
+    fn at(secs: i64) -> SystemTime {
+        if secs >= 0 {
+            UNIX_EPOCH + Duration::from_secs(secs as u64)
+        } else {
+            UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
+        }
+    }
+
+    #[test]
+    fn test_system_time() {
+        for &(s, secs) in &[("1970-01-01T00:00:00Z", 0),
+                            ("2000-02-29T00:00:00Z", 951_782_400),
+                            ("2100-03-01T00:00:00Z", 4_107_542_400),
+                            ("1969-07-20T20:17:40Z", -14_182_940),
+                            ("1900-03-01T00:00:00Z", -2_203_891_200)] {
+            let dt = parse(s);
+            assert_eq!(dt.to_system_time(), at(secs), "{}", s);
+            assert_eq!(IppDateTime::from_system_time(at(secs)).unwrap(), dt, "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_before_epoch_with_fraction() {
+        let dt = IppDateTime::from_system_time(UNIX_EPOCH - Duration::from_millis(500)).unwrap();
+        assert_eq!(dt.to_rfc3339(), "1969-12-31T23:59:59.5+00:00");
+        assert_eq!(dt.to_system_time(), UNIX_EPOCH - Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_leap_days() {
+        assert!(IppDateTime::new(2000, 2, 29, 0, 0, 0, 0, 0).is_ok());
+        assert!(IppDateTime::new(2024, 2, 29, 0, 0, 0, 0, 0).is_ok());
+        assert!(IppDateTime::new(2023, 2, 29, 0, 0, 0, 0, 0).is_err());
+        assert!(IppDateTime::new(2100, 2, 29, 0, 0, 0, 0, 0).is_err());
+
+        // the day after 2100-02-28 is 2100-03-01, 2000-02-28 is followed by a leap day
+        let next = |s: &str| IppDateTime::from_system_time(parse(s).to_system_time() + Duration::from_secs(86_400)).unwrap();
+        assert_eq!(next("2100-02-28T00:00:00Z"), parse("2100-03-01T00:00:00Z"));
+        assert_eq!(next("2000-02-28T00:00:00Z"), parse("2000-02-29T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_utc_offset() {
+        let dt = parse("2024-03-01T12:30:00.5+05:30");
+        assert_eq!(dt.utc_offset(), 330);
+        assert_eq!(dt.to_rfc3339(), "2024-03-01T12:30:00.5+05:30");
+
+        let utc = IppDateTime::from_system_time(dt.to_system_time()).unwrap();
+        assert_eq!(utc.to_rfc3339(), "2024-03-01T07:00:00.5+00:00");
+        assert_eq!(IppDateTime::from_system_time_with_offset(dt.to_system_time(), 330).unwrap(), dt);
+
+        // negative offset moving the local date to the previous day
+        let west = IppDateTime::from_system_time_with_offset(utc.to_system_time(), -480).unwrap();
+        assert_eq!(west.to_rfc3339(), "2024-02-29T23:00:00.5-08:00");
+        assert_eq!(west.to_system_time(), dt.to_system_time());
+    }
+
+    #[test]
+    fn test_negative_zero_offset() {
+        let dt = parse("2024-03-01T12:30:00-00:00");
+        assert_eq!(dt.utc_offset(), 0);
+        assert_eq!(dt.to_rfc3339(), "2024-03-01T12:30:00-00:00");
+        assert!(dt != parse("2024-03-01T12:30:00+00:00"));
+        assert_eq!(parse("2024-03-01T12:30:00Z"), parse("2024-03-01T12:30:00+00:00"));
+
+        let mut data = Vec::new();
+        dt.write(&mut data).unwrap();
+        assert_eq!(data[8], b'-');
+        assert_eq!(IppDateTime::from_bytes(&data).unwrap(), dt);
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        assert!(IppDateTime::new(2024, 0, 1, 0, 0, 0, 0, 0).is_err());
+        assert!(IppDateTime::new(2024, 13, 1, 0, 0, 0, 0, 0).is_err());
+        assert!(IppDateTime::new(2024, 4, 31, 0, 0, 0, 0, 0).is_err());
+        assert!(IppDateTime::new(2024, 1, 0, 0, 0, 0, 0, 0).is_err());
+        assert!(IppDateTime::new(2024, 1, 1, 24, 0, 0, 0, 0).is_err());
+        assert!(IppDateTime::new(2024, 1, 1, 0, 60, 0, 0, 0).is_err());
+        assert!(IppDateTime::new(2024, 1, 1, 0, 0, 61, 0, 0).is_err());
+        assert!(IppDateTime::new(2024, 1, 1, 0, 0, 0, 10, 0).is_err());
+        assert!(IppDateTime::new(2024, 1, 1, 0, 0, 0, 0, 15 * 60).is_err());
+        // leap second
+        assert!(IppDateTime::new(2016, 12, 31, 23, 59, 60, 0, 0).is_ok());
+
+        let valid = [0x07, 0xe8, 3, 1, 12, 30, 0, 0, b'+', 1, 0];
+        assert!(IppDateTime::from_bytes(&valid).is_ok());
+        assert!(IppDateTime::from_bytes(&valid[..10]).is_err());
+        let mut data = valid;
+        data[8] = b' ';
+        assert!(IppDateTime::from_bytes(&data).is_err());
+        let mut data = valid;
+        data[10] = 60;
+        assert!(IppDateTime::from_bytes(&data).is_err());
+    }
+
+    #[test]
+    fn test_malformed_rfc3339() {
+        for s in &["", "2024-03-01", "2024-03-01T12:30:00", "2024-3-01T12:30:00Z",
+                   "2024/03/01T12:30:00Z", "2024-03-01T12:30Z", "2024-03-01T12:30:00.Z",
+                   "2024-03-01T12:30:00+0100", "2024-03-01T12:30:00+01:00x", "+024-03-01T12:30:00Z",
+                   "2024-03-01T12:30:00\u{e9}", "2024-02-30T12:30:00Z", "2024-03-01T12:30:00+15:00"] {
+            assert!(IppDateTime::parse_rfc3339(s).is_err(), "{}", s);
+        }
+        assert_eq!(parse("2024-03-01t12:30:00.987z").to_rfc3339(), "2024-03-01T12:30:00.9+00:00");
+        assert_eq!("2024-03-01 12:30:00Z".parse::<IppDateTime>().unwrap(), parse("2024-03-01T12:30:00Z"));
+    }
+
+    #[test]
+    fn test_value_round_trip() {
+        let dt = parse("2024-03-01T12:30:00.5-03:30");
+        let mut data = Vec::new();
+        assert_eq!(IppValue::DateTime(dt).write(&mut data).unwrap(), 13);
+        assert_eq!(data, [0x00, 0x0b, 0x07, 0xe8, 3, 1, 12, 30, 0, 5, b'-', 3, 30]);
+
+        match IppValue::from_bytes(ValueTag::DateTime as u8, &data[2..]).unwrap() {
+            IppValue::DateTime(value) => assert_eq!(value, dt),
+            value => panic!("unexpected value {:?}", value)
+        }
+        // malformed dateTime values are kept as raw bytes
+        match IppValue::from_bytes(ValueTag::DateTime as u8, &data[2..12]).unwrap() {
+            IppValue::Other(tag, ref bytes) => assert_eq!((tag, &bytes[..]), (ValueTag::DateTime as u8, &data[2..12])),
+            value => panic!("unexpected value {:?}", value)
+        }
+    }
+}
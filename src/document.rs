@@ -0,0 +1,131 @@
+//!
+//! Owned document sources of Print-Job and Send-Document requests
+//!
+use std::fs::File;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+enum Source {
+    /// reader which can be read only once
+    Reader(Box<dyn Read + Send>),
+    /// seekable reader and its start position, known after the first read
+    Seekable(Box<dyn ReadSeek + Send>, Option<u64>),
+    /// file which is opened on the first read
+    File(PathBuf, Option<File>),
+    /// in-memory data
+    Buffer(Cursor<Vec<u8>>),
+}
+
+/// Document data which is sent after the attributes.
+/// Unlike a borrowed reader it can be stored, sent to another thread and rewound for resending.
+pub struct IppDocument {
+    source: Source,
+    /// data was read since the document was created or rewound
+    started: bool,
+    /// a one-shot reader was rewound after it was read from
+    exhausted: bool
+}
+
+fn not_rewindable() -> io::Error {
+    io::Error::other("document reader cannot be rewound")
+}
+
+impl IppDocument {
+    fn new(source: Source) -> IppDocument {
+        IppDocument { source, started: false, exhausted: false }
+    }
+
+    /// Create document from a reader which can be read only once
+    pub fn from_reader<R: Read + Send + 'static>(reader: R) -> IppDocument {
+        IppDocument::new(Source::Reader(Box::new(reader)))
+    }
+
+    /// Create document from a seekable reader, rewinding seeks back to its current position
+    pub fn from_seekable<R: Read + Seek + Send + 'static>(reader: R) -> IppDocument {
+        IppDocument::new(Source::Seekable(Box::new(reader), None))
+    }
+
+    /// Create document from a file which is opened when the document is read
+    pub fn from_file<P: AsRef<Path>>(path: P) -> IppDocument {
+        IppDocument::new(Source::File(path.as_ref().to_path_buf(), None))
+    }
+
+    /// Create document from in-memory data
+    pub fn from_bytes<B: Into<Vec<u8>>>(data: B) -> IppDocument {
+        IppDocument::new(Source::Buffer(Cursor::new(data.into())))
+    }
+
+    /// Check whether the document can be read again from the start
+    pub fn is_rewindable(&self) -> bool {
+        match self.source {
+            Source::Reader(_) => !self.started,
+            _ => true
+        }
+    }
+
+    /// Start reading from the beginning of the document again.
+    /// Fails for a one-shot reader which was already read from, further reads fail as well.
+    pub fn rewind(&mut self) -> io::Result<()> {
+        if self.started {
+            match self.source {
+                Source::Reader(_) => {
+                    self.exhausted = true;
+                    return Err(not_rewindable());
+                }
+                Source::Seekable(ref mut reader, Some(start)) => {
+                    reader.seek(SeekFrom::Start(start))?;
+                }
+                Source::Seekable(_, None) => {}
+                Source::File(_, ref mut file) => {
+                    if let Some(ref mut file) = *file {
+                        file.seek(SeekFrom::Start(0))?;
+                    }
+                }
+                Source::Buffer(ref mut cursor) => cursor.set_position(0),
+            }
+        }
+        self.started = false;
+        Ok(())
+    }
+}
+
+impl Read for IppDocument {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.exhausted {
+            return Err(not_rewindable());
+        }
+        self.started = true;
+        match self.source {
+            Source::Reader(ref mut reader) => reader.read(buf),
+            Source::Seekable(ref mut reader, ref mut start) => {
+                if start.is_none() {
+                    *start = Some(reader.stream_position()?);
+                }
+                reader.read(buf)
+            }
+            Source::File(ref path, ref mut file) => {
+                if file.is_none() {
+                    *file = Some(File::open(path)?);
+                }
+                file.as_mut().map_or(Ok(0), |file| file.read(buf))
+            }
+            Source::Buffer(ref mut cursor) => cursor.read(buf),
+        }
+    }
+}
+
+impl From<Vec<u8>> for IppDocument {
+    fn from(data: Vec<u8>) -> IppDocument {
+        IppDocument::from_bytes(data)
+    }
+}
+
+impl From<File> for IppDocument {
+    fn from(file: File) -> IppDocument {
+        IppDocument::from_seekable(file)
+    }
+}
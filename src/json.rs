@@ -0,0 +1,521 @@
+//!
+//! JSON representation of IPP messages based on the PWG IPP/JSON mapping, enabled by
+//! the `serde` feature
+//!
+//! Groups are kept in wire order as an array of `{"tag": ..., "attributes": ...}` objects,
+//! so that repeated and interleaved groups, such as the jobs of Get-Jobs, survive a round trip.
+//! Attributes are keyed by their name, of an attribute repeated within one group only the
+//! last value is kept. The keyed layout with one member per group tag,
+//! `"job-attributes-tag": [ { "job-id": 1 }, { "job-id": 2 } ]`, is accepted on input.
+//!
+//! ```text
+//! {
+//!   "version": "1.1", "operation-status": 2, "request-id": 1,
+//!   "groups": [
+//!     { "tag": "operation-attributes-tag", "attributes": {
+//!       "attributes-charset": { "type": "charset", "value": "utf-8" },
+//!       "copies": 2,
+//!       "sides-supported": [ "one-sided", "two-sided-long-edge" ],
+//!       "media-col": { "media-type": "stationery", "media-source": { "type": "no-value" } }
+//!     } },
+//!     { "tag": "job-attributes-tag", "attributes": { "job-id": 1 } },
+//!     { "tag": "job-attributes-tag", "attributes": { "job-id": 2 } }
+//!   ]
+//! }
+//! ```
+//!
+//! Values are native JSON values: numbers for integer, booleans, strings for keyword,
+//! arrays for 1setOf, objects for collection, null for no-value, `{"lower": 1, "upper": 10}`
+//! for rangeOfInteger, `{"xres": 600, "yres": 600, "units": "dpi"}` for resolution and
+//! `{"language": "en", "text": "..."}` or `{"language": "en", "name": "..."}` for
+//! language-tagged strings. Any other syntax is preserved with a `{"type": ..., "value": ...}`
+//! object, for example enum, text, uri or a collection which looks like one of the objects
+//! above. dateTime is RFC 3339 text, octetString and raw values are base64-encoded and
+//! out-of-band values have no `value`.
+//!
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::Error;
+use serde_json::{Map, Value};
+
+use enum_primitive::FromPrimitive;
+
+use ::{Result, IppError, IppHeader};
+use attribute::{IppAttribute, IppAttributeGroup, IppAttributeList};
+use consts::tag::{DelimiterTag, ValueTag};
+use datetime::IppDateTime;
+use request::{IppRequestResponse, IppRequestTrait};
+use value::IppValue;
+
+fn invalid(what: &str, json: &Value) -> IppError {
+    IppError::ValueError(format!("Invalid JSON {}: {}", what, json))
+}
+
+fn has_keys(map: &Map<String, Value>, keys: &[&str]) -> bool {
+    map.len() == keys.len() && keys.iter().all(|key| map.contains_key(*key))
+}
+
+// object with a syntax, the value is missing for out-of-band values
+fn is_typed(map: &Map<String, Value>) -> bool {
+    map.get("type").is_some_and(Value::is_string) &&
+        map.keys().all(|key| key == "type" || key == "value" || key == "tag")
+}
+
+// syntax of the native JSON value, None for arrays and typed objects
+fn native_tag(json: &Value) -> Option<ValueTag> {
+    let tag = match *json {
+        Value::Null => ValueTag::NoValue,
+        Value::Bool(_) => ValueTag::Boolean,
+        Value::Number(_) => ValueTag::Integer,
+        Value::String(_) => ValueTag::Keyword,
+        Value::Array(_) => return None,
+        Value::Object(ref map) => {
+            if is_typed(map) {
+                return None;
+            } else if has_keys(map, &["lower", "upper"]) {
+                ValueTag::RangeOfInteger
+            } else if has_keys(map, &["xres", "yres", "units"]) {
+                ValueTag::Resolution
+            } else if has_keys(map, &["language", "text"]) {
+                ValueTag::TextWithLanguage
+            } else if has_keys(map, &["language", "name"]) {
+                ValueTag::NameWithLanguage
+            } else {
+                ValueTag::BegCollection
+            }
+        }
+    };
+    Some(tag)
+}
+
+// value without its syntax, Null for out-of-band values
+fn native_value(value: &IppValue) -> Value {
+    match *value {
+        IppValue::Integer(i) | IppValue::Enum(i) => Value::from(i),
+        IppValue::Boolean(b) => Value::from(b),
+        IppValue::Keyword(ref s) |
+        IppValue::TextWithoutLanguage(ref s) | IppValue::NameWithoutLanguage(ref s) |
+        IppValue::Charset(ref s) | IppValue::NaturalLanguage(ref s) |
+        IppValue::Uri(ref s) | IppValue::MimeMediaType(ref s) |
+        IppValue::MemberAttrName(ref s) | IppValue::UriScheme(ref s) => Value::from(s.as_str()),
+        IppValue::TextWithLanguage { ref language, ref text } => json!({"language": language, "text": text}),
+        IppValue::NameWithLanguage { ref language, ref name } => json!({"language": language, "name": name}),
+        IppValue::RangeOfInteger(lower, upper) => json!({"lower": lower, "upper": upper}),
+        IppValue::Resolution(xres, yres, units) => {
+            let units = match units {
+                3 => Value::from("dpi"),
+                4 => Value::from("dpcm"),
+                _ => Value::from(units)
+            };
+            json!({"xres": xres, "yres": yres, "units": units})
+        }
+        IppValue::DateTime(ref dt) => Value::from(dt.to_rfc3339()),
+        IppValue::ListOf(ref list) => Value::Array(list.iter().map(value_to_json).collect()),
+        IppValue::Collection(ref members) => Value::Object(members.iter()
+            .map(|(name, value)| (name.clone(), value_to_json(value)))
+            .collect()),
+        IppValue::OctetString(ref data) | IppValue::Extension(_, ref data) |
+        IppValue::Other(_, ref data) => Value::from(BASE64.encode(data)),
+        IppValue::Unsupported | IppValue::Unknown | IppValue::NoValue |
+        IppValue::NotSettable | IppValue::DeleteAttribute | IppValue::AdminDefine => Value::Null,
+    }
+}
+
+fn value_to_json(value: &IppValue) -> Value {
+    let native = native_value(value);
+    match *value {
+        IppValue::ListOf(_) => return native,
+        _ if native_tag(&native).is_some_and(|tag| tag as u8 == value.to_tag()) => return native,
+        _ => {}
+    }
+
+    let mut map = Map::new();
+    let syntax = match *value {
+        IppValue::Other(tag, _) => format!("0x{:02x}", tag),
+        _ => value.syntax().to_string()
+    };
+    map.insert("type".to_string(), Value::from(syntax));
+    if let IppValue::Extension(tag, _) = *value {
+        map.insert("tag".to_string(), Value::from(tag));
+    }
+    if !value.is_out_of_band() {
+        map.insert("value".to_string(), native);
+    }
+    Value::Object(map)
+}
+
+fn get_str<'a>(json: &'a Value, key: &str) -> Result<&'a str> {
+    json.get(key).and_then(Value::as_str).ok_or_else(|| invalid(key, json))
+}
+
+fn to_i32(json: &Value) -> Option<i32> {
+    json.as_i64().and_then(|i| if i32::MIN as i64 <= i && i <= i32::MAX as i64 { Some(i as i32) } else { None })
+}
+
+fn get_i32(json: &Value, key: &str) -> Result<i32> {
+    json.get(key).and_then(to_i32).ok_or_else(|| invalid(key, json))
+}
+
+// value of the given syntax from its native JSON value
+fn syntax_value(tag: ValueTag, json: &Value) -> Result<IppValue> {
+    let string = || json.as_str().map(str::to_string).ok_or_else(|| invalid("string", json));
+    let integer = || to_i32(json).ok_or_else(|| invalid("integer", json));
+    let base64 = || json.as_str().and_then(|s| BASE64.decode(s).ok()).ok_or_else(|| invalid("base64 value", json));
+    let retval = match tag {
+        ValueTag::Integer => IppValue::Integer(integer()?),
+        ValueTag::Enum => IppValue::Enum(integer()?),
+        ValueTag::Boolean => IppValue::Boolean(json.as_bool().ok_or_else(|| invalid("boolean", json))?),
+        ValueTag::Keyword => IppValue::Keyword(string()?),
+        ValueTag::TextWithoutLanguage => IppValue::TextWithoutLanguage(string()?),
+        ValueTag::NameWithoutLanguage => IppValue::NameWithoutLanguage(string()?),
+        ValueTag::Charset => IppValue::Charset(string()?),
+        ValueTag::NaturalLanguage => IppValue::NaturalLanguage(string()?),
+        ValueTag::Uri => IppValue::Uri(string()?),
+        ValueTag::UriScheme => IppValue::UriScheme(string()?),
+        ValueTag::MimeMediaType => IppValue::MimeMediaType(string()?),
+        ValueTag::MemberAttrName => IppValue::MemberAttrName(string()?),
+        ValueTag::TextWithLanguage => IppValue::TextWithLanguage {
+            language: get_str(json, "language")?.to_string(),
+            text: get_str(json, "text")?.to_string()
+        },
+        ValueTag::NameWithLanguage => IppValue::NameWithLanguage {
+            language: get_str(json, "language")?.to_string(),
+            name: get_str(json, "name")?.to_string()
+        },
+        ValueTag::RangeOfInteger => IppValue::RangeOfInteger(get_i32(json, "lower")?, get_i32(json, "upper")?),
+        ValueTag::Resolution => {
+            let units = match json.get("units") {
+                Some(Value::String(s)) if s == "dpi" => 3,
+                Some(Value::String(s)) if s == "dpcm" => 4,
+                _ => json.get("units").and_then(Value::as_i64)
+                    .and_then(|units| if i8::MIN as i64 <= units && units <= i8::MAX as i64 { Some(units as i8) } else { None })
+                    .ok_or_else(|| invalid("units", json))?
+            };
+            IppValue::Resolution(get_i32(json, "xres")?, get_i32(json, "yres")?, units)
+        }
+        ValueTag::DateTime => IppValue::DateTime(IppDateTime::parse_rfc3339(&string()?)?),
+        ValueTag::BegCollection => {
+            let members = json.as_object().ok_or_else(|| invalid("collection", json))?;
+            IppValue::Collection(members.iter()
+                .map(|(name, member)| Ok((name.clone(), value_from_json(member)?)))
+                .collect::<Result<_>>()?)
+        }
+        ValueTag::OctectStringUnspecified => IppValue::OctetString(base64()?),
+        ValueTag::Unsupported => IppValue::Unsupported,
+        ValueTag::Unknown => IppValue::Unknown,
+        ValueTag::NoValue => IppValue::NoValue,
+        ValueTag::NotSettable => IppValue::NotSettable,
+        ValueTag::DeleteAttribute => IppValue::DeleteAttribute,
+        ValueTag::AdminDefine => IppValue::AdminDefine,
+        ValueTag::Extension | ValueTag::EndCollection => return Err(invalid("syntax", json)),
+    };
+    Ok(retval)
+}
+
+// value given as {"type": ..., "value": ...}
+fn typed_value(json: &Value) -> Result<IppValue> {
+    let syntax = get_str(json, "type")?;
+    let value = json.get("value").unwrap_or(&Value::Null);
+    let base64 = || value.as_str().and_then(|s| BASE64.decode(s).ok()).ok_or_else(|| invalid("base64 value", json));
+    if let Some(hex) = syntax.strip_prefix("0x") {
+        let tag = u8::from_str_radix(hex, 16).map_err(|_| invalid("type", json))?;
+        return Ok(IppValue::Other(tag, base64()?));
+    }
+    match ValueTag::from_name(syntax) {
+        Some(ValueTag::Extension) => {
+            let tag = json.get("tag").and_then(Value::as_u64)
+                .and_then(|tag| if tag <= u64::from(u32::MAX) { Some(tag as u32) } else { None })
+                .ok_or_else(|| invalid("tag", json))?;
+            Ok(IppValue::Extension(tag, base64()?))
+        }
+        Some(tag) => syntax_value(tag, value),
+        None => Err(invalid("type", json))
+    }
+}
+
+fn value_from_json(json: &Value) -> Result<IppValue> {
+    match *json {
+        Value::Array(ref items) => Ok(IppValue::ListOf(items.iter().map(value_from_json).collect::<Result<_>>()?)),
+        Value::Object(ref map) if is_typed(map) => typed_value(json),
+        _ => match native_tag(json) {
+            Some(tag) => syntax_value(tag, json),
+            None => Err(invalid("value", json))
+        }
+    }
+}
+
+fn group_name(tag: DelimiterTag) -> String {
+    tag.name().map_or_else(|| format!("0x{:02x}", tag as u8), str::to_string)
+}
+
+fn group_tag(name: &str) -> Option<DelimiterTag> {
+    match name.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16).ok().and_then(DelimiterTag::from_u8),
+        None => DelimiterTag::from_name(name)
+    }
+}
+
+fn group_to_json(group: &IppAttributeGroup) -> Value {
+    Value::Object(group.iter().map(|attr| (attr.name().to_string(), value_to_json(attr.value()))).collect())
+}
+
+fn group_from_json(tag: DelimiterTag, json: &Value) -> Result<IppAttributeGroup> {
+    let attributes = json.as_object().ok_or_else(|| invalid("group", json))?;
+    let mut group = IppAttributeGroup::new(tag);
+    for (name, value) in attributes {
+        group.push(IppAttribute::new(name, value_from_json(value)?));
+    }
+    Ok(group)
+}
+
+fn tag_from_json(name: &str) -> Result<DelimiterTag> {
+    match group_tag(name) {
+        Some(DelimiterTag::EndOfAttributes) | None => Err(invalid("group tag", &Value::from(name))),
+        Some(tag) => Ok(tag)
+    }
+}
+
+// groups in wire order
+fn groups_to_json(attributes: &IppAttributeList, map: &mut Map<String, Value>) {
+    let groups = attributes.groups().iter()
+        .map(|group| json!({"tag": group_name(group.tag()), "attributes": group_to_json(group)}))
+        .collect();
+    map.insert("groups".to_string(), Value::Array(groups));
+}
+
+// groups from the "groups" array, or else from the members whose names end with "-tag"
+fn groups_from_json(json: &Value) -> Result<IppAttributeList> {
+    let map = json.as_object().ok_or_else(|| invalid("groups", json))?;
+    let mut retval = IppAttributeList::new();
+    if let Some(groups) = map.get("groups") {
+        for group in groups.as_array().ok_or_else(|| invalid("groups", groups))? {
+            let tag = tag_from_json(get_str(group, "tag")?)?;
+            let attributes = group.get("attributes").ok_or_else(|| invalid("attributes", group))?;
+            retval.add_group(group_from_json(tag, attributes)?);
+        }
+        return Ok(retval);
+    }
+    for (name, groups) in map.iter().filter(|(name, _)| name.ends_with("-tag") || name.starts_with("0x")) {
+        let tag = tag_from_json(name)?;
+        match *groups {
+            Value::Array(ref groups) => for group in groups {
+                retval.add_group(group_from_json(tag, group)?);
+            },
+            ref group => retval.add_group(group_from_json(tag, group)?)
+        }
+    }
+    Ok(retval)
+}
+
+impl IppValue {
+    /// Convert to native JSON value, or to an object with `type` and `value` fields
+    /// if the syntax cannot be told from the native value
+    pub fn to_json(&self) -> Value {
+        value_to_json(self)
+    }
+
+    /// Convert from native JSON value or from an object with `type` and `value` fields
+    pub fn from_json(json: &Value) -> Result<IppValue> {
+        value_from_json(json)
+    }
+}
+
+impl IppAttributeList {
+    /// Convert to JSON object with the groups in wire order
+    pub fn to_json(&self) -> Value {
+        let mut map = Map::new();
+        groups_to_json(self, &mut map);
+        Value::Object(map)
+    }
+
+    /// Convert from JSON object with the ordered groups or with groups keyed by tag name
+    pub fn from_json(json: &Value) -> Result<IppAttributeList> {
+        groups_from_json(json)
+    }
+}
+
+impl<'a> IppRequestResponse<'a> {
+    /// Convert header and attributes to JSON, the payload is not included
+    pub fn to_json(&self) -> Value {
+        let header = self.header();
+        let mut map = Map::new();
+        map.insert("version".to_string(), Value::from(format!("{}.{}", header.version >> 8, header.version & 0xff)));
+        map.insert("operation-status".to_string(), Value::from(header.operation_status));
+        map.insert("request-id".to_string(), Value::from(header.request_id));
+        groups_to_json(self.attributes(), &mut map);
+        Value::Object(map)
+    }
+
+    /// Create request or response from JSON, without payload
+    pub fn from_json(json: &Value) -> Result<IppRequestResponse<'a>> {
+        let version = get_str(json, "version")?;
+        let version = match version.find('.') {
+            Some(pos) => version[..pos].parse::<u8>().ok()
+                .and_then(|major| version[pos + 1..].parse::<u8>().ok().map(|minor| u16::from(major) << 8 | u16::from(minor))),
+            None => None
+        }.ok_or_else(|| invalid("version", json))?;
+        let operation_status = json.get("operation-status").and_then(Value::as_u64)
+            .filter(|&n| n <= u64::from(u16::MAX))
+            .ok_or_else(|| invalid("operation-status", json))?;
+        let request_id = json.get("request-id").and_then(Value::as_u64)
+            .filter(|&n| n <= u64::from(u32::MAX))
+            .ok_or_else(|| invalid("request-id", json))?;
+        let attributes = groups_from_json(json)?;
+
+        let header = IppHeader::new(version, operation_status as u16, request_id as u32);
+        Ok(IppRequestResponse::from_parts(header, attributes))
+    }
+}
+
+// serde support goes through serde_json::Value
+macro_rules! json_serde {
+    ($t:ty $(, $lt:lifetime)*) => {
+        impl<$($lt),*> Serialize for $t {
+            fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+                self.to_json().serialize(serializer)
+            }
+        }
+
+        impl<'de, $($lt),*> Deserialize<'de> for $t {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+                let json = Value::deserialize(deserializer)?;
+                Self::from_json(&json).map_err(|e| D::Error::custom(e))
+            }
+        }
+    }
+}
+
+json_serde!(IppValue);
+json_serde!(IppAttributeList);
+json_serde!(IppRequestResponse<'a>, 'a);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use consts::attribute::ATTRIBUTES_CHARSET;
+
+    fn round_trip(value: IppValue) {
+        let json = value.to_json();
+        let parsed = IppValue::from_json(&json).unwrap_or_else(|e| panic!("{}: {}", json, e));
+        assert_eq!(parsed.to_text(), value.to_text(), "{}", json);
+        assert_eq!(parsed.to_json(), json);
+    }
+
+    #[test]
+    fn test_native_values() {
+        assert_eq!(IppValue::Integer(5).to_json(), json!(5));
+        assert_eq!(IppValue::Boolean(true).to_json(), json!(true));
+        assert_eq!(IppValue::Keyword("a4".into()).to_json(), json!("a4"));
+        assert_eq!(IppValue::NoValue.to_json(), Value::Null);
+        assert_eq!(IppValue::RangeOfInteger(1, 10).to_json(), json!({"lower": 1, "upper": 10}));
+        assert_eq!(IppValue::Enum(3).to_json(), json!({"type": "enum", "value": 3}));
+        assert_eq!(IppValue::Unknown.to_json(), json!({"type": "unknown"}));
+        assert_eq!(IppValue::ListOf(vec![IppValue::Keyword("one".into()), IppValue::Integer(2)]).to_json(),
+                   json!(["one", 2]));
+        assert_eq!(IppValue::Collection(vec![("media-type".into(), IppValue::Keyword("stationery".into()))]).to_json(),
+                   json!({"media-type": "stationery"}));
+    }
+
+    #[test]
+    fn test_value_round_trip() {
+        round_trip(IppValue::Enum(3));
+        round_trip(IppValue::Resolution(600, 300, 3));
+        round_trip(IppValue::Resolution(600, 300, 7));
+        round_trip(IppValue::TextWithoutLanguage("text".into()));
+        round_trip(IppValue::NameWithLanguage { language: "en".into(), name: "name".into() });
+        round_trip(IppValue::Uri("ipp://localhost/printers/test".into()));
+        round_trip(IppValue::DateTime(IppDateTime::parse_rfc3339("2020-01-02T03:04:05Z").unwrap()));
+        round_trip(IppValue::ListOf(vec![IppValue::Keyword("a4".into()), IppValue::NameWithoutLanguage("x".into()),
+                                         IppValue::NoValue]));
+        round_trip(IppValue::ListOf(vec![IppValue::Integer(1)]));
+        round_trip(IppValue::Extension(0x1234, vec![1, 2, 3]));
+        round_trip(IppValue::Other(0x7f, vec![0xff]));
+        // collections which look like other values keep their syntax
+        round_trip(IppValue::Collection(vec![("lower".into(), IppValue::Integer(1)),
+                                             ("upper".into(), IppValue::Integer(2))]));
+        round_trip(IppValue::Collection(vec![("type".into(), IppValue::Keyword("x".into()))]));
+        round_trip(IppValue::Collection(vec![]));
+    }
+
+    #[test]
+    fn test_base64_round_trip() {
+        for len in 0..8 {
+            let data: Vec<u8> = (0..len).map(|i| (i * 37 + 250) as u8).collect();
+            let value = IppValue::OctetString(data.clone());
+            match IppValue::from_json(&value.to_json()).unwrap() {
+                IppValue::OctetString(parsed) => assert_eq!(parsed, data),
+                other => panic!("unexpected value {:?}", other)
+            }
+        }
+        assert_eq!(IppValue::OctetString(b"ipp".to_vec()).to_json(), json!({"type": "octetString", "value": "aXBw"}));
+        assert!(IppValue::from_json(&json!({"type": "octetString", "value": "a*=="})).is_err());
+    }
+
+    #[test]
+    fn test_groups() {
+        let mut attributes = IppAttributeList::new();
+        attributes.add(DelimiterTag::OperationAttributes,
+                       IppAttribute::new(ATTRIBUTES_CHARSET, IppValue::Charset("utf-8".into())));
+        for id in 1..3 {
+            let mut group = IppAttributeGroup::new(DelimiterTag::JobAttributes);
+            group.add(IppAttribute::new("job-id", IppValue::Integer(id)));
+            attributes.add_group(group);
+        }
+        let mut request = IppRequestResponse::from_parts(IppHeader::new(0x0101, 2, 1), attributes);
+        request.set_attribute(DelimiterTag::PrinterAttributes, IppAttribute::new("copies", 2));
+
+        let json = request.to_json();
+        assert_eq!(json, json!({
+            "version": "1.1", "operation-status": 2, "request-id": 1,
+            "groups": [
+                {"tag": "operation-attributes-tag", "attributes": {"attributes-charset": {"type": "charset", "value": "utf-8"}}},
+                {"tag": "job-attributes-tag", "attributes": {"job-id": 1}},
+                {"tag": "job-attributes-tag", "attributes": {"job-id": 2}},
+                {"tag": "printer-attributes-tag", "attributes": {"copies": 2}}
+            ]
+        }));
+
+        let parsed = IppRequestResponse::from_json(&json).unwrap();
+        assert_eq!(parsed.attributes().groups_of(DelimiterTag::JobAttributes).count(), 2);
+        assert_eq!(parsed.to_json(), json);
+    }
+
+    #[test]
+    fn test_interleaved_groups() {
+        let mut attributes = IppAttributeList::new();
+        for &(tag, id) in &[(DelimiterTag::JobAttributes, 1), (DelimiterTag::PrinterAttributes, 2),
+                            (DelimiterTag::JobAttributes, 3)] {
+            let mut group = IppAttributeGroup::new(tag);
+            group.add(IppAttribute::new("id", IppValue::Integer(id)));
+            attributes.add_group(group);
+        }
+
+        let parsed = IppAttributeList::from_json(&attributes.to_json()).unwrap();
+        let groups: Vec<(DelimiterTag, i32)> = parsed.groups().iter()
+            .map(|group| match *group.get("id").unwrap().value() {
+                IppValue::Integer(id) => (group.tag(), id),
+                ref value => panic!("unexpected value {:?}", value)
+            })
+            .collect();
+        assert_eq!(groups, [(DelimiterTag::JobAttributes, 1), (DelimiterTag::PrinterAttributes, 2),
+                            (DelimiterTag::JobAttributes, 3)]);
+    }
+
+    #[test]
+    fn test_keyed_groups() {
+        let json = json!({
+            "version": "2.0", "operation-status": 0, "request-id": 7,
+            "operation-attributes-tag": {"attributes-charset": {"type": "charset", "value": "utf-8"}},
+            "job-attributes-tag": [{"job-id": 1}, {"job-id": 2}]
+        });
+        let parsed = IppRequestResponse::from_json(&json).unwrap();
+        assert_eq!(parsed.header().version, 0x0200);
+        assert_eq!(parsed.attributes().groups_of(DelimiterTag::JobAttributes).count(), 2);
+        assert_eq!(parsed.to_json()["groups"][2], json!({"tag": "job-attributes-tag", "attributes": {"job-id": 2}}));
+
+        assert!(IppAttributeList::from_json(&json!({"groups": [{"tag": "end-of-attributes-tag", "attributes": {}}]})).is_err());
+        assert!(IppAttributeList::from_json(&json!({"groups": [{"tag": "job-attributes-tag"}]})).is_err());
+        assert!(IppAttributeList::from_json(&json!({"groups": {"job-attributes-tag": {}}})).is_err());
+    }
+}
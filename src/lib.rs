@@ -3,9 +3,16 @@
 //!
 //! Usage examples:
 //!
-//!```rust
+//!```rust,no_run
+//! # extern crate ipp;
+//! # use ipp::{IppClient, IppRequestResponse, GetPrinterAttributes};
+//! # use ipp::request::IppRequestTrait;
+//! # use ipp::consts::operation::Operation;
+//! # use ipp::consts::tag::DelimiterTag;
+//! # fn main() {
 //! // using raw API
-//! let mut req = IppRequestResponse::new(Operation::GetPrinterAttributes);
+//! let mut req = IppRequestResponse::new(Operation::GetPrinterAttributes,
+//!                                       "http://localhost:631/printers/test-printer");
 //! let client = IppClient::new("http://localhost:631/printers/test-printer");
 //! let resp = client.send_request(&mut req).unwrap();
 //! if resp.header().operation_status <= 3 {
@@ -16,10 +23,10 @@
 //! let operation = GetPrinterAttributes::new();
 //! let client = IppClient::new("http://localhost:631/printers/test-printer");
 //! let attrs = client.send(operation).unwrap();
-//! for (_, v) in attrs.get_group(DelimiterTag::PrinterAttributes).unwrap() {
+//! for v in attrs.get_group(DelimiterTag::PrinterAttributes).unwrap() {
 //!     println!("{}: {}", v.name(), v.value());
 //! }
-
+//! # }
 //!```
 
 extern crate byteorder;
@@ -28,7 +35,18 @@ extern crate hyper;
 
 #[macro_use]
 extern crate log;
+#[cfg(feature = "serde")]
+extern crate base64;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde_json;
+#[cfg(feature = "xml")]
+extern crate xml;
 
+use std::error;
+use std::fmt;
 use std::result;
 use std::io::{self, Read, Write};
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
@@ -42,18 +60,33 @@ pub mod consts {
 }
 
 pub mod value;
+pub mod datetime;
+pub mod charset;
+mod convert;
+mod text;
+#[cfg(feature = "serde")]
+mod json;
+#[cfg(feature = "xml")]
+pub mod sm;
 pub mod parser;
+pub mod borrowed;
+pub mod document;
+pub mod target;
 pub mod request;
 pub mod attribute;
 pub mod client;
 pub mod server;
 pub mod operation;
 
-pub use attribute::{IppAttribute, IppAttributeList};
+pub use attribute::{IppAttribute, IppAttributeGroup, IppAttributeList};
 pub use client::IppClient;
 pub use operation::{IppOperation, PrintJob, GetPrinterAttributes, CreateJob, SendDocument};
-pub use request::IppRequestResponse;
+pub use request::{IppRequestResponse, IppOwnedRequest};
+pub use document::IppDocument;
+pub use target::IppTarget;
 pub use value::IppValue;
+pub use datetime::IppDateTime;
+pub use charset::IppCharset;
 pub const IPP_VERSION: u16 = 0x0101;
 
 use consts::statuscode::StatusCode;
@@ -65,10 +98,43 @@ pub enum IppError {
     IOError(::std::io::Error),
     RequestError(String),
     AttributeError(String),
+    /// Value does not have the requested syntax
+    ValueError(String),
+    /// Attribute name or value is longer than the wire format or the syntax allows
+    LengthError { attribute: String, length: usize, limit: usize },
     StatusError(consts::statuscode::StatusCode),
+    /// Malformed IPP stream detected by the parser
+    ParseError(parser::ParseError),
     TagError(u8)
 }
 
+impl fmt::Display for IppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IppError::HttpError(ref e) => write!(f, "HTTP error: {}", e),
+            IppError::IOError(ref e) => write!(f, "I/O error: {}", e),
+            IppError::RequestError(ref s) => write!(f, "Request error: {}", s),
+            IppError::AttributeError(ref s) => write!(f, "Attribute error: {}", s),
+            IppError::ValueError(ref s) => write!(f, "Value error: {}", s),
+            IppError::LengthError { ref attribute, length, limit } =>
+                write!(f, "Length {} of attribute '{}' exceeds {}", length, attribute, limit),
+            IppError::StatusError(ref code) => write!(f, "IPP status {:?}", code),
+            IppError::ParseError(ref e) => write!(f, "Parse error: {}", e),
+            IppError::TagError(tag) => write!(f, "Invalid tag 0x{:02x}", tag),
+        }
+    }
+}
+
+impl error::Error for IppError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            IppError::HttpError(ref e) => Some(e),
+            IppError::IOError(ref e) => Some(e),
+            _ => None
+        }
+    }
+}
+
 impl From<io::Error> for IppError {
     fn from(error: io::Error) -> IppError {
         IppError::IOError(error)
@@ -98,7 +164,7 @@ pub struct IppHeader {
 }
 
 impl IppHeader {
-    pub fn from_reader(reader: &mut Read) -> Result<IppHeader> {
+    pub fn from_reader(reader: &mut dyn Read) -> Result<IppHeader> {
         let retval = IppHeader::new(
             reader.read_u16::<BigEndian>()?,
             reader.read_u16::<BigEndian>()?,
@@ -108,10 +174,10 @@ impl IppHeader {
 
     /// Create IPP header
     pub fn new(version: u16, status: u16, request_id: u32) -> IppHeader {
-        IppHeader {version: version, operation_status: status, request_id: request_id}
+        IppHeader {version, operation_status: status, request_id}
     }
 
-    pub fn write(&self, writer: &mut Write) -> Result<usize> {
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize> {
         writer.write_u16::<BigEndian>(self.version)?;
         writer.write_u16::<BigEndian>(self.operation_status)?;
         writer.write_u32::<BigEndian>(self.request_id)?;
@@ -127,8 +193,7 @@ pub trait ReadIppExt: Read {
     }
 
     fn read_vec(&mut self, len: usize) -> std::io::Result<Vec<u8>> {
-        let mut namebuf: Vec<u8> = Vec::with_capacity(len);
-        unsafe { namebuf.set_len(len) };
+        let mut namebuf: Vec<u8> = vec![0; len];
 
         self.read_exact(&mut namebuf)?;
 
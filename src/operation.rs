@@ -2,9 +2,11 @@
 //! High-level IPP operation abstractions
 //!
 
-use std::io::Read;
+use ::Result;
 use attribute::IppAttribute;
+use document::IppDocument;
 use request::IppRequestResponse;
+use target::IppTarget;
 use value::IppValue;
 use consts::tag::*;
 use consts::operation::*;
@@ -12,30 +14,31 @@ use consts::attribute::*;
 
 /// Trait which represents a single IPP operation
 pub trait IppOperation {
-    /// Convert this operation to IPP request which is ready for sending
-    fn to_ipp_request(&mut self, uri: &str) -> IppRequestResponse;
+    /// Convert this operation to IPP request which is ready for sending.
+    /// A document is rewound first, so the operation can be sent again.
+    fn to_ipp_request(&mut self, uri: &str) -> Result<IppRequestResponse<'_>>;
 }
 
 /// IPP operation Print-Job
-pub struct PrintJob<'a> {
-    reader: &'a mut Read,
+pub struct PrintJob {
+    document: IppDocument,
     user_name: String,
     job_name: Option<String>,
     attributes: Vec<IppAttribute>
 }
 
-impl<'a> PrintJob<'a> {
+impl PrintJob {
     /// Create Print-Job operation
     ///
-    /// * `reader` - [std::io::Read](https://doc.rust-lang.org/stable/std/io/trait.Read.html) reference which points to the data to be printed<br/>
+    /// * `document` - data to be printed, for example a `File` or `IppDocument::from_file`<br/>
     /// * `user_name` - name of the user (requesting-user-name)<br/>
     /// * `job_name` - optional job name (job-name)<br/>
-    pub fn new(reader: &'a mut Read,
-               user_name: &str, job_name: Option<&str>) -> PrintJob<'a> {
+    pub fn new<T: Into<IppDocument>>(document: T,
+               user_name: &str, job_name: Option<&str>) -> PrintJob {
         PrintJob {
-            reader: reader,
+            document: document.into(),
             user_name: user_name.to_string(),
-            job_name: if let Some(name) = job_name { Some(name.to_string()) } else { None },
+            job_name: job_name.map(|name| name.to_string()),
             attributes: Vec::new()
         }
     }
@@ -46,8 +49,8 @@ impl<'a> PrintJob<'a> {
     }
 }
 
-impl<'a> IppOperation for PrintJob<'a> {
-    fn to_ipp_request(&mut self, uri: &str) -> IppRequestResponse {
+impl IppOperation for PrintJob {
+    fn to_ipp_request(&mut self, uri: &str) -> Result<IppRequestResponse<'_>> {
         let mut retval = IppRequestResponse::new(Operation::PrintJob, uri);
 
         retval.set_attribute(DelimiterTag::OperationAttributes,
@@ -63,8 +66,10 @@ impl<'a> IppOperation for PrintJob<'a> {
         for attr in &self.attributes {
             retval.set_attribute(DelimiterTag::JobAttributes, attr.clone());
         }
-        retval.set_payload(self.reader);
-        retval
+        // a resent operation starts from the beginning
+        self.document.rewind()?;
+        retval.set_payload(&mut self.document);
+        Ok(retval)
     }
 }
 
@@ -88,7 +93,7 @@ impl GetPrinterAttributes {
 }
 
 impl IppOperation for GetPrinterAttributes {
-    fn to_ipp_request(&mut self, uri: &str) -> IppRequestResponse {
+    fn to_ipp_request(&mut self, uri: &str) -> Result<IppRequestResponse<'_>> {
         let mut retval = IppRequestResponse::new(Operation::GetPrinterAttributes, uri);
 
         if !self.attributes.is_empty() {
@@ -97,7 +102,7 @@ impl IppOperation for GetPrinterAttributes {
                 IppAttribute::new(REQUESTED_ATTRIBUTES, IppValue::ListOf(vals)));
         }
 
-        retval
+        Ok(retval)
     }
 }
 
@@ -113,7 +118,7 @@ impl CreateJob {
     /// * `job_name` - optional job name (job-name)<br/>
     pub fn new(job_name: Option<&str>) -> CreateJob {
         CreateJob {
-            job_name: if let Some(name) = job_name { Some(name.to_string()) } else { None },
+            job_name: job_name.map(|name| name.to_string()),
             attributes: Vec::new()
         }
     }
@@ -127,7 +132,7 @@ impl CreateJob {
 }
 
 impl IppOperation for CreateJob {
-    fn to_ipp_request(&mut self, uri: &str) -> IppRequestResponse {
+    fn to_ipp_request(&mut self, uri: &str) -> Result<IppRequestResponse<'_>> {
         let mut retval = IppRequestResponse::new(Operation::CreateJob, uri);
 
         if let Some(ref job_name) = self.job_name {
@@ -139,42 +144,40 @@ impl IppOperation for CreateJob {
         for attr in &self.attributes {
             retval.set_attribute(DelimiterTag::JobAttributes, attr.clone());
         }
-        retval
+        Ok(retval)
     }
 }
 
 /// IPP operation Print-Job
-pub struct SendDocument<'a> {
+pub struct SendDocument {
     job_id: i32,
-    reader: &'a mut Read,
+    document: IppDocument,
     user_name: String,
     last: bool
 }
 
-impl<'a> SendDocument<'a> {
+impl SendDocument {
     /// Create Send-Document operation
     ///
     /// * `job_id` - job ID returned by Create-Job operation<br/>
-    /// * `reader` - [std::io::Read](https://doc.rust-lang.org/stable/std/io/trait.Read.html) reference which points to the data to be printed<br/>
+    /// * `document` - data to be printed, for example a `File` or `IppDocument::from_file`<br/>
     /// * `user_name` - name of the user (requesting-user-name)<br/>
     /// * `last` - whether this document is a last one<br/>
-    pub fn new(job_id: i32, reader: &'a mut Read,
-               user_name: &str, last: bool) -> SendDocument<'a> {
+    pub fn new<T: Into<IppDocument>>(job_id: i32, document: T,
+               user_name: &str, last: bool) -> SendDocument {
         SendDocument {
-            job_id: job_id,
-            reader: reader,
+            job_id,
+            document: document.into(),
             user_name: user_name.to_string(),
-            last: last
+            last
         }
     }
 }
 
-impl<'a> IppOperation for SendDocument<'a> {
-    fn to_ipp_request(&mut self, uri: &str) -> IppRequestResponse {
-        let mut retval = IppRequestResponse::new(Operation::SendDocument, uri);
-
-        retval.set_attribute(DelimiterTag::OperationAttributes,
-            IppAttribute::new(JOB_ID, IppValue::Integer(self.job_id)));
+impl IppOperation for SendDocument {
+    fn to_ipp_request(&mut self, uri: &str) -> Result<IppRequestResponse<'_>> {
+        let target = IppTarget::PrinterJob(uri.to_string(), self.job_id);
+        let mut retval = IppRequestResponse::new_with_target(Operation::SendDocument, &target);
 
         retval.set_attribute(DelimiterTag::OperationAttributes,
             IppAttribute::new(REQUESTING_USER_NAME,
@@ -184,8 +187,28 @@ impl<'a> IppOperation for SendDocument<'a> {
             IppAttribute::new(LAST_DOCUMENT,
                 IppValue::Boolean(self.last)));
 
-        retval.set_payload(self.reader);
+        // a resent operation starts from the beginning
+        self.document.rewind()?;
+        retval.set_payload(&mut self.document);
+
+        Ok(retval)
+    }
+}
 
-        retval
+#[cfg(test)]
+mod tests {
+    use request::IppRequestTrait;
+    use super::*;
+
+    #[test]
+    fn test_rewind_error_is_returned() {
+        let mut operation = PrintJob::new(IppDocument::from_reader(&b"data"[..]), "user", None);
+        let mut data = Vec::new();
+        operation.to_ipp_request("ipp://localhost/printer").unwrap()
+            .payload().unwrap().read_to_end(&mut data).unwrap();
+        assert_eq!(data, b"data");
+
+        // a one-shot reader which was already read from cannot be sent again
+        assert!(operation.to_ipp_request("ipp://localhost/printer").is_err());
     }
 }
@@ -1,31 +1,873 @@
 //!
 //! IPP stream parser
 //!
-use std::io::Read;
-use byteorder::{BigEndian, ReadBytesExt};
+use std::fmt;
+use std::io::{self, Read};
+use std::result;
+use byteorder::{BigEndian, ByteOrder, ReadBytesExt};
 
 use enum_primitive::FromPrimitive;
 
 use ::{Result, IppError, IppHeader, ReadIppExt};
-use attribute::{IppAttribute, IppAttributeList};
-use value::IppValue;
+use attribute::{IppAttribute, IppAttributeGroup, IppAttributeList};
+use value::{self, IppValue, MAX_LENGTH};
+use datetime::IppDateTime;
+use charset::IppCharset;
 use consts::tag::*;
 use consts::statuscode::StatusCode;
+use consts::attribute::ATTRIBUTES_CHARSET;
 
-fn list_to_value(mut list: Vec<IppValue>) -> IppValue {
-    if list.len() == 1 { list.remove(0) } else { IppValue::ListOf(list) }
+/// Value assembled by the parser state: `IppValue`, or `IppValueRef` which borrows
+/// from the message buffer
+pub(crate) trait StateValue: Sized + Clone {
+    /// Attribute and member name
+    type Name: AsRef<str> + Clone;
+    /// Attribute group
+    type Group;
+
+    fn list(values: Vec<Self>) -> Self;
+    fn collection(members: Vec<(Self::Name, Self)>) -> Self;
+    fn tag(&self) -> u8;
+    /// name given by a memberAttrName value
+    fn member_name(self) -> Option<Self::Name>;
+    /// charset name given by a charset value
+    fn charset(&self) -> Option<&str>;
+    /// text of a keyword value
+    fn keyword(&self) -> Option<&str>;
+
+    fn new_group(tag: DelimiterTag) -> Self::Group;
+    fn group_tag(group: &Self::Group) -> DelimiterTag;
+    fn group_contains(group: &Self::Group, name: &str) -> bool;
+    /// append attribute to the group in wire order, attributes with the same name are kept
+    fn group_add(group: &mut Self::Group, name: Self::Name, value: Self);
+}
+
+/// Decoding of names and value fields which live at least as long as `'d`
+pub(crate) trait DecodeValue<'d>: StateValue {
+    fn decode_name(data: &'d [u8]) -> Self::Name;
+    fn decode(tag: u8, data: &'d [u8], charset: IppCharset) -> Result<Self>;
+}
+
+impl StateValue for IppValue {
+    type Name = String;
+    type Group = IppAttributeGroup;
+
+    fn list(values: Vec<IppValue>) -> IppValue {
+        IppValue::ListOf(values)
+    }
+
+    fn collection(members: Vec<(String, IppValue)>) -> IppValue {
+        IppValue::Collection(members)
+    }
+
+    fn tag(&self) -> u8 {
+        self.to_tag()
+    }
+
+    fn member_name(self) -> Option<String> {
+        match self {
+            IppValue::MemberAttrName(name) => Some(name),
+            _ => None
+        }
+    }
+
+    fn charset(&self) -> Option<&str> {
+        match *self {
+            IppValue::Charset(ref charset) => Some(charset),
+            _ => None
+        }
+    }
+
+    fn keyword(&self) -> Option<&str> {
+        match *self {
+            IppValue::Keyword(ref keyword) => Some(keyword),
+            _ => None
+        }
+    }
+
+    fn new_group(tag: DelimiterTag) -> IppAttributeGroup {
+        IppAttributeGroup::new(tag)
+    }
+
+    fn group_tag(group: &IppAttributeGroup) -> DelimiterTag {
+        group.tag()
+    }
+
+    fn group_contains(group: &IppAttributeGroup, name: &str) -> bool {
+        group.get(name).is_some()
+    }
+
+    fn group_add(group: &mut IppAttributeGroup, name: String, value: IppValue) {
+        group.push(IppAttribute::new(&name, value));
+    }
+}
+
+impl<'d> DecodeValue<'d> for IppValue {
+    fn decode_name(data: &'d [u8]) -> String {
+        String::from_utf8_lossy(data).into_owned()
+    }
+
+    fn decode(tag: u8, data: &'d [u8], charset: IppCharset) -> Result<IppValue> {
+        IppValue::from_bytes_with_charset(tag, data, charset)
+    }
+}
+
+// values keep their own tags, so a heterogeneous 1setOf is preserved as is
+fn list_to_value<V: StateValue>(mut list: Vec<V>) -> Option<V> {
+    match list.len() {
+        0 => None,
+        1 => Some(list.remove(0)),
+        _ => Some(V::list(list))
+    }
+}
+
+/// Decoder validation mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Decode as much as possible without validation
+    #[default]
+    Normal,
+    /// Reject malformed values and message structure
+    Strict,
+    /// Recover from common firmware bugs and record each deviation as a warning
+    Lenient,
+}
+
+/// Resource limits which bound the memory used for a single message.
+/// Exceeding a limit is an error in every parse mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// Maximum size of the header and attributes, document data is not counted
+    pub max_message_size: usize,
+    /// Maximum number of attributes, collection members included
+    pub max_attributes: usize,
+    /// Maximum number of values of a single attribute or collection member
+    pub max_values: usize,
+    /// Maximum length of an attribute name
+    pub max_name_length: usize,
+    /// Maximum length of a single value
+    pub max_value_length: usize,
+    /// Maximum nesting depth of collections
+    pub max_depth: usize,
+}
+
+impl Default for ParseLimits {
+    fn default() -> ParseLimits {
+        ParseLimits {
+            max_message_size: 4 * 1024 * 1024,
+            max_attributes: 10_000,
+            max_values: 16_384,
+            max_name_length: MAX_LENGTH,
+            max_value_length: MAX_LENGTH,
+            max_depth: 32,
+        }
+    }
+}
+
+impl ParseLimits {
+    /// Limits which accept any message the wire format can express, for peers which are trusted,
+    /// such as the printer a client talks to. Collection nesting stays bounded by the default
+    /// depth because nested values are written and dropped recursively.
+    pub fn unlimited() -> ParseLimits {
+        ParseLimits {
+            max_message_size: usize::MAX,
+            max_attributes: usize::MAX,
+            max_values: usize::MAX,
+            ..ParseLimits::default()
+        }
+    }
+}
+
+/// Kind of the error detected by the parser
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// Value length does not match the fixed size of its syntax
+    InvalidLength { tag: u8, expected: usize, actual: usize },
+    /// Boolean value other than 0 or 1
+    InvalidBoolean(u8),
+    /// dateTime value with out of range fields
+    InvalidDateTime,
+    /// Additional value without a preceding attribute or member name
+    MissingAttributeName,
+    /// Attribute outside of any group
+    MissingGroup,
+    /// Group which is not allowed at its position: operation attributes must come first and
+    /// only once, unsupported attributes at most once right after them
+    GroupOrder(DelimiterTag),
+    /// Unbalanced begCollection/endCollection or memberAttrName outside of a collection
+    InvalidCollection,
+    /// Attribute which occurs more than once in the same group
+    DuplicateAttribute,
+    /// Additional value whose tag does not match the tag of the first value
+    MixedValueTags { first: u8, tag: u8 },
+    /// Keyword value with characters outside of US-ASCII
+    NonAsciiKeyword,
+    /// Stream ended without the end-of-attributes tag
+    MissingEndOfAttributes,
+    /// `attributes-charset` names a charset which is not supported, text is decoded as utf-8
+    UnsupportedCharset(String),
+    /// Stream ended in the middle of the header or a tag
+    UnexpectedEof,
+    /// Tag which is neither a known delimiter nor a value tag
+    InvalidTag(u8),
+    /// Value which cannot be decoded according to its tag
+    InvalidValue(String),
+    /// Header and attributes exceed `ParseLimits::max_message_size`
+    MessageTooLarge { limit: usize },
+    /// Message has more attributes than `ParseLimits::max_attributes`
+    TooManyAttributes { limit: usize },
+    /// Attribute has more values than `ParseLimits::max_values`
+    TooManyValues { limit: usize },
+    /// Attribute name is longer than `ParseLimits::max_name_length`
+    NameTooLong { length: usize, limit: usize },
+    /// Value is longer than `ParseLimits::max_value_length`
+    ValueTooLong { length: usize, limit: usize },
+    /// Collections are nested deeper than `ParseLimits::max_depth`
+    NestingTooDeep { limit: usize },
+}
+
+impl ParseErrorKind {
+    /// Status code which a server should respond with when the request fails with this error
+    pub fn status_code(&self) -> StatusCode {
+        match *self {
+            ParseErrorKind::NameTooLong { .. } | ParseErrorKind::ValueTooLong { .. } =>
+                StatusCode::ClientErrorRequestValueTooLong,
+            ParseErrorKind::MessageTooLarge { .. } | ParseErrorKind::TooManyAttributes { .. } |
+            ParseErrorKind::TooManyValues { .. } | ParseErrorKind::NestingTooDeep { .. } =>
+                StatusCode::ClientErrorRequestEntityTooLong,
+            _ => StatusCode::ClientErrorBadRequest
+        }
+    }
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseErrorKind::InvalidLength { tag, expected, actual } =>
+                write!(f, "value with tag 0x{:02x} has length {}, expected {}", tag, actual, expected),
+            ParseErrorKind::InvalidBoolean(value) => write!(f, "invalid boolean value {}", value),
+            ParseErrorKind::InvalidDateTime => write!(f, "invalid dateTime value"),
+            ParseErrorKind::MissingAttributeName => write!(f, "additional value without attribute name"),
+            ParseErrorKind::MissingGroup => write!(f, "attribute outside of any group"),
+            ParseErrorKind::GroupOrder(tag) => write!(f, "unexpected group {}", GroupName(tag)),
+            ParseErrorKind::InvalidCollection => write!(f, "unbalanced collection"),
+            ParseErrorKind::DuplicateAttribute => write!(f, "duplicate attribute"),
+            ParseErrorKind::MixedValueTags { first, tag } =>
+                write!(f, "value tag 0x{:02x} does not match the first value tag 0x{:02x}", tag, first),
+            ParseErrorKind::NonAsciiKeyword => write!(f, "keyword with non-ASCII characters"),
+            ParseErrorKind::MissingEndOfAttributes => write!(f, "missing end-of-attributes-tag"),
+            ParseErrorKind::UnsupportedCharset(ref name) => write!(f, "unsupported charset '{}'", name),
+            ParseErrorKind::UnexpectedEof => write!(f, "unexpected end of stream"),
+            ParseErrorKind::InvalidTag(tag) => write!(f, "invalid tag 0x{:02x}", tag),
+            ParseErrorKind::InvalidValue(ref reason) => write!(f, "invalid value: {}", reason),
+            ParseErrorKind::MessageTooLarge { limit } => write!(f, "message exceeds {} bytes", limit),
+            ParseErrorKind::TooManyAttributes { limit } => write!(f, "more than {} attributes", limit),
+            ParseErrorKind::TooManyValues { limit } => write!(f, "more than {} values", limit),
+            ParseErrorKind::NameTooLong { length, limit } =>
+                write!(f, "name length {} exceeds {}", length, limit),
+            ParseErrorKind::ValueTooLong { length, limit } =>
+                write!(f, "value length {} exceeds {}", length, limit),
+            ParseErrorKind::NestingTooDeep { limit } =>
+                write!(f, "collections nested deeper than {}", limit),
+        }
+    }
+}
+
+/// Group tag as it appears in messages, named if known
+struct GroupName(DelimiterTag);
+
+impl fmt::Display for GroupName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "0x{:02x}", self.0 as u8)
+        }
+    }
+}
+
+/// write the position of a parse error or warning
+fn write_context(f: &mut fmt::Formatter, offset: usize, group: Option<DelimiterTag>,
+                 attribute: Option<&str>, tag: Option<u8>) -> fmt::Result {
+    write!(f, " at offset {}", offset)?;
+    if let Some(group) = group {
+        write!(f, " in {}", GroupName(group))?;
+    }
+    if let Some(attribute) = attribute {
+        write!(f, ", attribute '{}'", attribute)?;
+    }
+    if let Some(tag) = tag {
+        write!(f, ", tag 0x{:02x}", tag)?;
+    }
+    Ok(())
+}
+
+/// Parse error with the position where it was detected
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset of the failing tag from the start of the stream
+    pub offset: usize,
+    /// Group being decoded, if any
+    pub group: Option<DelimiterTag>,
+    /// Name of the attribute being decoded, if any
+    pub attribute: Option<String>,
+    /// Tag being decoded, None while the header is decoded
+    pub tag: Option<u8>,
+    /// What went wrong
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        write_context(f, self.offset, self.group, self.attribute.as_deref(), self.tag)
+    }
+}
+
+/// Deviation from the IPP encoding which was recovered from in lenient mode
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseWarning {
+    /// Byte offset of the offending tag from the start of the stream
+    pub offset: usize,
+    /// Group being decoded, if any
+    pub group: Option<DelimiterTag>,
+    /// Name of the attribute being decoded, if any
+    pub attribute: Option<String>,
+    /// Tag being decoded, if any
+    pub tag: Option<u8>,
+    /// What was wrong
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        write_context(f, self.offset, self.group, self.attribute.as_deref(), self.tag)
+    }
+}
+
+type StateResult = result::Result<(), ParseErrorKind>;
+
+// keyword and name values, as well as text values with and without language,
+// legitimately share a 1setOf; out-of-band values may replace any value
+fn is_compatible_tag(first: u8, tag: u8) -> bool {
+    fn family(tag: u8) -> u8 {
+        match ValueTag::from_u8(tag) {
+            Some(ValueTag::Keyword) | Some(ValueTag::NameWithoutLanguage) |
+            Some(ValueTag::NameWithLanguage) => ValueTag::Keyword as u8,
+            Some(ValueTag::TextWithLanguage) => ValueTag::TextWithoutLanguage as u8,
+            _ => tag
+        }
+    }
+    let out_of_band = |tag| (0x10..0x20).contains(&tag);
+    out_of_band(first) || out_of_band(tag) || family(first) == family(tag)
+}
+
+// value checks which do not depend on the parser state
+fn check_value(tag: u8, data: &[u8]) -> StateResult {
+    if let Some(expected) = value::fixed_length(tag) {
+        if data.len() != expected {
+            return Err(ParseErrorKind::InvalidLength { tag, expected, actual: data.len() });
+        }
+    }
+    if tag == ValueTag::Boolean as u8 && data[0] > 1 {
+        return Err(ParseErrorKind::InvalidBoolean(data[0]));
+    }
+    if tag == ValueTag::DateTime as u8 && IppDateTime::from_bytes(data).is_err() {
+        return Err(ParseErrorKind::InvalidDateTime);
+    }
+    Ok(())
+}
+
+/// Attribute the tag being processed belongs to. The name is kept as decoded
+/// and only copied when an error or warning is reported.
+enum AttributeContext<N> {
+    /// delimiter tag, stray memberAttrName or endCollection
+    None,
+    /// additional value or collection member of the pending attribute
+    Pending,
+    /// attribute named by the tag itself
+    Named(N),
+}
+
+/// Read wrapper which keeps track of the stream offset
+struct CountingReader<'a> {
+    inner: &'a mut dyn Read,
+    offset: usize
+}
+
+impl<'a> Read for CountingReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let size = self.inner.read(buf)?;
+        self.offset += size;
+        Ok(size)
+    }
+}
+
+/// Collection which is being parsed
+struct CollectionFrame<V: StateValue> {
+    /// completed members
+    members: Vec<(V::Name, V)>,
+    /// name of the current member, set by memberAttrName
+    member_name: Option<V::Name>,
+    /// values of the current member
+    values: Vec<V>
+}
+
+impl<V: StateValue> CollectionFrame<V> {
+    fn new() -> CollectionFrame<V> {
+        CollectionFrame { members: Vec::new(), member_name: None, values: Vec::new() }
+    }
+
+    fn finish_member(&mut self) {
+        if let Some(name) = self.member_name.take() {
+            if let Some(value) = list_to_value(::std::mem::take(&mut self.values)) {
+                self.members.push((name, value));
+            }
+        }
+    }
+
+    fn into_value(mut self) -> V {
+        self.finish_member();
+        V::collection(self.members)
+    }
+}
+
+/// Parser state which assembles groups, attributes and collections from decoded values,
+/// shared by the pull, push and borrowing parsers
+pub(crate) struct ParseState<V: StateValue = IppValue> {
+    /// validation mode
+    mode: ParseMode,
+    /// resource limits
+    limits: ParseLimits,
+    /// number of attributes and collection members started so far
+    count: usize,
+    /// offset of the tag being processed
+    offset: usize,
+    /// tag being processed, None while the header is processed
+    tag: Option<u8>,
+    /// attribute the tag being processed belongs to
+    attribute: AttributeContext<V::Name>,
+    /// completed groups
+    groups: Vec<V::Group>,
+    /// deviations recorded in lenient mode
+    warnings: Vec<ParseWarning>,
+    /// current attribute group, None until the first delimiter tag
+    group: Option<V::Group>,
+    /// name of the current attribute
+    name: Option<V::Name>,
+    /// values of the current attribute
+    values: Vec<V>,
+    /// stack of collections being parsed, innermost last
+    stack: Vec<CollectionFrame<V>>,
+    /// charset of text and name values, set by the attributes-charset operation attribute
+    charset: IppCharset,
+    /// keep a copy of every completed attribute in `completed`, used by the push parser
+    /// to hand out attributes before the group is complete
+    emit: bool,
+    /// attributes completed since they were last taken
+    completed: Vec<(DelimiterTag, V::Name, V)>
+}
+
+impl<V: StateValue> ParseState<V> {
+    pub(crate) fn new(mode: ParseMode, limits: ParseLimits) -> ParseState<V> {
+        ParseState {
+            mode,
+            limits,
+            count: 0,
+            offset: 0,
+            tag: None,
+            attribute: AttributeContext::None,
+            groups: Vec::new(),
+            warnings: Vec::new(),
+            group: None,
+            name: None,
+            values: Vec::new(),
+            stack: Vec::new(),
+            charset: IppCharset::default(),
+            emit: false,
+            completed: Vec::new()
+        }
+    }
+
+    /// Return completed groups
+    pub(crate) fn into_groups(self) -> Vec<V::Group> {
+        self.groups
+    }
+
+    /// start processing the tag at the given stream offset, None while the header is processed
+    pub(crate) fn start_tag(&mut self, offset: usize, tag: Option<u8>) {
+        self.offset = offset;
+        self.tag = tag;
+        self.attribute = AttributeContext::None;
+    }
+
+    /// set the attribute of the value tag being processed: its own name, the pending attribute
+    /// for an additional value or a collection member, none for a stray memberAttrName or endCollection
+    fn set_attribute(&mut self, tag: u8, name: V::Name) {
+        self.attribute = if !name.as_ref().is_empty() {
+            AttributeContext::Named(name)
+        } else if self.stack.is_empty() &&
+            (tag == ValueTag::MemberAttrName as u8 || tag == ValueTag::EndCollection as u8) {
+            AttributeContext::None
+        } else {
+            AttributeContext::Pending
+        };
+    }
+
+    /// name of the attribute the tag being processed belongs to
+    fn attribute(&self) -> Option<String> {
+        match self.attribute {
+            AttributeContext::None => None,
+            AttributeContext::Pending => self.name.as_ref().map(|name| name.as_ref().to_string()),
+            AttributeContext::Named(ref name) => Some(name.as_ref().to_string())
+        }
+    }
+
+    /// build parse error in the context of the tag being processed
+    pub(crate) fn error(&self, kind: ParseErrorKind) -> IppError {
+        IppError::ParseError(ParseError {
+            offset: self.offset,
+            group: self.group_tag(),
+            attribute: self.attribute(),
+            tag: self.tag,
+            kind
+        })
+    }
+
+    /// build parse error from the I/O error, only a truncated stream is a parse error
+    fn io_error(&self, error: io::Error) -> IppError {
+        if error.kind() == io::ErrorKind::UnexpectedEof {
+            self.error(ParseErrorKind::UnexpectedEof)
+        } else {
+            IppError::IOError(error)
+        }
+    }
+
+    fn group_tag(&self) -> Option<DelimiterTag> {
+        self.group.as_ref().map(V::group_tag)
+    }
+
+    /// record a deviation which is only reported in lenient mode
+    fn warn(&mut self, kind: ParseErrorKind) {
+        if self.mode == ParseMode::Lenient {
+            let warning = ParseWarning {
+                offset: self.offset,
+                group: self.group_tag(),
+                attribute: self.attribute(),
+                tag: self.tag,
+                kind
+            };
+            debug!("Parse warning: {}", warning);
+            self.warnings.push(warning);
+        }
+    }
+
+    /// deviation which is an error in strict mode and a warning in lenient mode
+    fn deviation(&mut self, kind: ParseErrorKind) -> StateResult {
+        if self.mode == ParseMode::Strict {
+            return Err(kind);
+        }
+        self.warn(kind);
+        Ok(())
+    }
+
+    /// check token which ends at the given stream offset before its data is read
+    fn check_token(&self, end: usize, namelen: usize, vsize: usize) -> Result<()> {
+        let limits = &self.limits;
+        let kind = if namelen > limits.max_name_length {
+            ParseErrorKind::NameTooLong { length: namelen, limit: limits.max_name_length }
+        } else if vsize > limits.max_value_length {
+            ParseErrorKind::ValueTooLong { length: vsize, limit: limits.max_value_length }
+        } else if end > limits.max_message_size {
+            ParseErrorKind::MessageTooLarge { limit: limits.max_message_size }
+        } else {
+            return Ok(());
+        };
+        Err(self.error(kind))
+    }
+
+    /// count attribute or collection member
+    fn count_attribute(&mut self) -> StateResult {
+        if self.count >= self.limits.max_attributes {
+            return Err(ParseErrorKind::TooManyAttributes { limit: self.limits.max_attributes });
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    /// put the pending attribute into the current group
+    fn finish_attribute(&mut self) -> StateResult {
+        if let Some(name) = self.name.take() {
+            let value = list_to_value(::std::mem::take(&mut self.values));
+            if let (Some(group), Some(value)) = (self.group.as_mut(), value) {
+                if self.emit {
+                    self.completed.push((V::group_tag(group), name.clone(), value.clone()));
+                }
+                V::group_add(group, name, value);
+            }
+        }
+        Ok(())
+    }
+
+    /// every delimiter tag closes the current group, even if the tag repeats
+    fn delimiter(&mut self, tag: Option<DelimiterTag>) -> StateResult {
+        if !self.stack.is_empty() {
+            self.deviation(ParseErrorKind::InvalidCollection)?;
+            // drop unterminated collections
+            self.stack.clear();
+        }
+        self.finish_attribute()?;
+        if let Some(group) = self.group.take() {
+            self.groups.push(group);
+        }
+
+        if let Some(tag) = tag {
+            // RFC 8011 4.1.1 and 4.2: operation attributes come first and only once,
+            // unsupported attributes at most once right after them, then the other groups
+            let previous = self.groups.last().map(V::group_tag);
+            let valid = match tag {
+                DelimiterTag::OperationAttributes => previous.is_none(),
+                DelimiterTag::UnsupportedAttributes => previous == Some(DelimiterTag::OperationAttributes),
+                _ => previous.is_some()
+            };
+            if !valid {
+                self.deviation(ParseErrorKind::GroupOrder(tag))?;
+            }
+        }
+
+        self.group = tag.map(V::new_group);
+        Ok(())
+    }
+
+    /// start new attribute if the name is not empty, otherwise it's an additional value
+    fn attribute_name(&mut self, name: V::Name) -> StateResult {
+        if self.group.is_none() {
+            // assume the operation attributes group
+            self.deviation(ParseErrorKind::MissingGroup)?;
+            self.group = Some(V::new_group(DelimiterTag::OperationAttributes));
+        }
+        if name.as_ref().is_empty() && self.name.is_none() {
+            self.deviation(ParseErrorKind::MissingAttributeName)?;
+        }
+        if !name.as_ref().is_empty() {
+            self.count_attribute()?;
+            self.finish_attribute()?;
+            if self.group.as_ref().is_some_and(|group| V::group_contains(group, name.as_ref())) {
+                // every occurrence is kept in wire order, lookups return the first one
+                self.deviation(ParseErrorKind::DuplicateAttribute)?;
+            }
+            self.name = Some(name);
+        }
+        Ok(())
+    }
+
+    fn push_value(&mut self, value: V) -> StateResult {
+        let count = self.stack.last().map_or(self.values.len(), |frame| frame.values.len());
+        if count >= self.limits.max_values {
+            return Err(ParseErrorKind::TooManyValues { limit: self.limits.max_values });
+        }
+        if value.keyword().is_some_and(|keyword| !keyword.is_ascii()) {
+            self.warn(ParseErrorKind::NonAsciiKeyword);
+        }
+        match self.stack.last_mut() {
+            Some(frame) => {
+                if frame.member_name.is_none() {
+                    self.deviation(ParseErrorKind::MissingAttributeName)?;
+                    // values without member name are dropped
+                    return Ok(());
+                }
+                frame.values.push(value);
+            }
+            None => {
+                if self.name.is_none() {
+                    // values without attribute name are dropped
+                    return Ok(());
+                }
+                if let Some(charset) = value.charset() {
+                    if self.is_charset_attribute() {
+                        match IppCharset::from_name(charset) {
+                            Some(charset) => self.charset = charset,
+                            None => self.warn(ParseErrorKind::UnsupportedCharset(charset.to_string()))
+                        }
+                    }
+                }
+                let first = self.values.first().map(V::tag);
+                if let Some(first) = first {
+                    let tag = value.tag();
+                    if !is_compatible_tag(first, tag) {
+                        self.warn(ParseErrorKind::MixedValueTags { first, tag });
+                    }
+                }
+                self.values.push(value);
+            }
+        }
+        Ok(())
+    }
+
+    fn is_charset_attribute(&self) -> bool {
+        self.name.as_ref().is_some_and(|name| name.as_ref() == ATTRIBUTES_CHARSET) &&
+            self.group.as_ref().is_some_and(|group| V::group_tag(group) == DelimiterTag::OperationAttributes)
+    }
+
+    fn begin_collection(&mut self, name: V::Name) -> StateResult {
+        if self.stack.is_empty() {
+            self.attribute_name(name)?;
+        } else if self.stack.last().is_some_and(|frame| frame.member_name.is_none()) {
+            self.deviation(ParseErrorKind::MissingAttributeName)?;
+        }
+        if self.stack.len() >= self.limits.max_depth {
+            return Err(ParseErrorKind::NestingTooDeep { limit: self.limits.max_depth });
+        }
+        self.stack.push(CollectionFrame::new());
+        Ok(())
+    }
+
+    fn end_collection(&mut self) -> StateResult {
+        match self.stack.pop() {
+            Some(frame) => {
+                let value = frame.into_value();
+                self.push_value(value)
+            }
+            None => self.deviation(ParseErrorKind::InvalidCollection)
+        }
+    }
+
+    fn value(&mut self, tag: u8, name: V::Name, value: V) -> StateResult {
+        if self.stack.is_empty() {
+            if tag == ValueTag::MemberAttrName as u8 {
+                self.deviation(ParseErrorKind::InvalidCollection)?;
+            }
+            self.attribute_name(name)?;
+        } else if tag == ValueTag::MemberAttrName as u8 {
+            self.count_attribute()?;
+            if let Some(frame) = self.stack.last_mut() {
+                frame.finish_member();
+                if let Some(member) = value.member_name() {
+                    frame.member_name = Some(member);
+                }
+            }
+            return Ok(());
+        }
+        self.push_value(value)
+    }
+}
+
+// processing of complete tags, shared by the pull, push and borrowing parsers
+impl<V: StateValue> ParseState<V> {
+    /// treat end of stream at a tag boundary as end of attributes, lenient mode only
+    fn end_of_stream(&mut self) -> Result<()> {
+        self.warn(ParseErrorKind::MissingEndOfAttributes);
+        self.delimiter(None).map_err(|kind| self.error(kind))
+    }
+
+    /// process delimiter tag, returns true at the end of attributes
+    fn delimiter_tag(&mut self, tag: u8) -> Result<bool> {
+        debug!("Delimiter tag: {:0x}", tag);
+
+        if tag == DelimiterTag::EndOfAttributes as u8 {
+            self.delimiter(None).map_err(|kind| self.error(kind))?;
+            return Ok(true);
+        }
+
+        let delimiter = DelimiterTag::from_u8(tag).ok_or_else(|| self.error(ParseErrorKind::InvalidTag(tag)))?;
+        self.delimiter(Some(delimiter)).map_err(|kind| self.error(kind))?;
+        Ok(false)
+    }
+
+    /// process value tag with its name and value field
+    fn value_tag<'d>(&mut self, tag: u8, name: V::Name, data: &'d [u8]) -> Result<()> where V: DecodeValue<'d> {
+        if let Err(kind) = check_value(tag, data) {
+            self.deviation(kind).map_err(|kind| self.error(kind))?;
+        }
+
+        let result = if tag == ValueTag::BegCollection as u8 {
+            debug!("Begin collection: {}", name.as_ref());
+            self.begin_collection(name)
+        } else if tag == ValueTag::EndCollection as u8 {
+            debug!("End collection");
+            self.end_collection()
+        } else {
+            let value = V::decode(tag, data, self.charset)
+                .map_err(|e| {
+                    let reason = match e {
+                        IppError::IOError(_) => "value is truncated".to_string(),
+                        e => e.to_string()
+                    };
+                    self.error(ParseErrorKind::InvalidValue(reason))
+                })?;
+            debug!("Value tag: {:0x}: {}", tag, name.as_ref());
+            self.value(tag, name, value)
+        };
+        result.map_err(|kind| self.error(kind))
+    }
+
+    /// Process the tag at the start of the slice, which starts at the given stream offset.
+    /// Returns the size of the tag and whether it ends the attributes, None if the slice ends
+    /// in the middle of the tag.
+    pub(crate) fn slice_tag<'d>(&mut self, offset: usize, buf: &'d [u8]) -> Result<Option<(usize, bool)>>
+        where V: DecodeValue<'d> {
+        let tag = match buf.first() {
+            Some(&tag) => tag,
+            None => return Ok(None)
+        };
+        self.start_tag(offset, Some(tag));
+
+        if is_delimiter_tag(tag) {
+            self.check_token(offset + 1, 0, 0)?;
+            let end = self.delimiter_tag(tag)?;
+            Ok(Some((1, end)))
+        } else if is_value_tag(tag) {
+            let field = |start: usize| buf.get(start..start + 2).map(|b| BigEndian::read_u16(b) as usize);
+            let namelen = match field(1) {
+                Some(len) => len,
+                None => return Ok(None)
+            };
+            self.check_token(offset + 3 + namelen, namelen, 0)?;
+            let vsize = match field(3 + namelen) {
+                Some(len) => len,
+                None => return Ok(None)
+            };
+            let name = V::decode_name(&buf[3..3 + namelen]);
+            self.set_attribute(tag, name.clone());
+            let size = 5 + namelen + vsize;
+            self.check_token(offset + size, namelen, vsize)?;
+            if buf.len() < size {
+                return Ok(None);
+            }
+            self.value_tag(tag, name, &buf[5 + namelen..size])?;
+            Ok(Some((size, false)))
+        } else {
+            Err(self.error(ParseErrorKind::InvalidTag(tag)))
+        }
+    }
+}
+
+impl ParseState<IppValue> {
+    fn into_result(self, header: IppHeader) -> IppParseResult {
+        let mut attributes = IppAttributeList::new();
+        for group in self.groups {
+            attributes.add_group(group);
+        }
+        IppParseResult { header, attributes, warnings: self.warnings }
+    }
 }
 
 /// IPP parsing result
 pub struct IppParseResult {
     header: IppHeader,
-    attributes: IppAttributeList
+    attributes: IppAttributeList,
+    warnings: Vec<ParseWarning>
 }
 
 impl IppParseResult {
     /// Create instance of the parsing result
     pub fn new(header: IppHeader, attributes: IppAttributeList) -> IppParseResult {
-        IppParseResult {header: header, attributes: attributes}
+        IppParseResult {header, attributes, warnings: Vec::new()}
+    }
+
+    /// Get deviations recovered from in lenient mode
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
     }
 
     /// Get parsed header
@@ -37,94 +879,593 @@ impl IppParseResult {
     pub fn attributes(&self) -> &IppAttributeList {
         &self.attributes
     }
+
+    /// Split into header and attributes without copying
+    pub fn into_parts(self) -> (IppHeader, IppAttributeList) {
+        (self.header, self.attributes)
+    }
 }
 
 /// IPP parser implementation
 pub struct IppParser<'a> {
-    reader: &'a mut Read
+    reader: &'a mut dyn Read,
+    mode: ParseMode,
+    limits: ParseLimits
 }
 
 impl<'a> IppParser<'a> {
     /// Create IPP parser using the given Read
-    pub fn new(reader: &'a mut Read) -> IppParser<'a> {
-        IppParser { reader: reader }
+    pub fn new(reader: &'a mut dyn Read) -> IppParser<'a> {
+        IppParser { reader, mode: ParseMode::default(), limits: ParseLimits::default() }
     }
 
-    /// Parse IPP stream
-    pub fn parse(&mut self) -> Result<IppParseResult> {
-        // last delimiter tag
-        let mut delimiter = DelimiterTag::EndOfAttributes;
+    /// Set validation mode, `ParseMode::Normal` by default
+    pub fn set_mode(&mut self, mode: ParseMode) {
+        self.mode = mode;
+    }
 
-        // stack of current attributes context. Used with lists and collections
-        let mut stack = vec![vec![]];
+    /// Set resource limits, `ParseLimits::default()` by default
+    pub fn set_limits(&mut self, limits: ParseLimits) {
+        self.limits = limits;
+    }
 
-        // holds the result of parsing
-        let mut retval = IppAttributeList::new();
+    /// Return the underlying reader. After a successful parse it is positioned
+    /// at the start of the document data.
+    pub fn into_reader(self) -> &'a mut dyn Read {
+        self.reader
+    }
 
-        // name of previous attribute name
-        let mut last_name: Option<String> = None;
+    /// Parse IPP stream
+    pub fn parse(&mut self) -> Result<IppParseResult> {
+        let mut state = ParseState::new(self.mode, self.limits);
+        let mut reader = CountingReader { inner: self.reader, offset: 0 };
 
         // parse IPP header
-        let header = IppHeader::from_reader(self.reader)?;
+        let header = match IppHeader::from_reader(&mut reader) {
+            Err(IppError::IOError(e)) => return Err(state.io_error(e)),
+            result => result?
+        };
         debug!("IPP reply header: {:?}", header);
 
         loop {
-            let tag = self.reader.read_u8()?;
-            if is_delimiter_tag(tag) {
-                debug!("Delimiter tag: {:0x}", tag);
-                if tag == DelimiterTag::EndOfAttributes as u8 {
-                    // end of stream, get last saved collection
-                    if let Some(last_name) = last_name {
-                        if let Some(val_list) = stack.pop() {
-                            retval.add(delimiter, IppAttribute::new(&last_name, list_to_value(val_list)));
-                        }
+            state.start_tag(reader.offset, None);
+            let tag = match reader.read_u8() {
+                Ok(tag) => tag,
+                Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    if self.mode != ParseMode::Lenient {
+                        return Err(state.error(ParseErrorKind::MissingEndOfAttributes));
                     }
+                    state.end_of_stream()?;
                     break;
-                } else {
-                    // remember delimiter tag
-                    delimiter = DelimiterTag::from_u8(tag).ok_or(StatusCode::ClientErrorBadRequest)?;
                 }
-            } else if is_value_tag(tag) {
-                // value tag
-                let namelen = self.reader.read_u16::<BigEndian>()?;
-                let name = self.reader.read_string(namelen as usize)?;
-                let value = IppValue::read(tag, &mut self.reader)?;
-
-                debug!("Value tag: {:0x}: {}: {}", tag, name, value);
-
-                if namelen > 0 {
-                    // single attribute or begin of array
-                    if let Some(last_name) = last_name {
-                        // put the previous attribute into the retval
-                        if let Some(val_list) = stack.pop() {
-                            retval.add(delimiter, IppAttribute::new(&last_name, list_to_value(val_list)));
-                        }
-                        stack.push(vec![]);
-                    }
-                    // store it as a previous attribute
-                    last_name = Some(name);
+                Err(e) => return Err(e.into())
+            };
+            state.tag = Some(tag);
+            if is_delimiter_tag(tag) {
+                state.check_token(reader.offset, 0, 0)?;
+                if state.delimiter_tag(tag)? {
+                    break;
                 }
-                if tag == ValueTag::BegCollection as u8 {
-                    // start new collection in the stack
-                    debug!("Begin collection");
-                    stack.push(vec![])
-                } else if tag == ValueTag::EndCollection as u8 {
-                    // get collection from the stack and add it to the previous element
-                    debug!("End collection");
-                    if let Some(arr) = stack.pop() {
-                        if let Some(val_list) = stack.last_mut() {
-                            val_list.push(IppValue::Collection(arr));
-                        }
+            } else if is_value_tag(tag) {
+                let namelen = reader.read_u16::<BigEndian>().map_err(|e| state.io_error(e))? as usize;
+                state.check_token(reader.offset + namelen, namelen, 0)?;
+                let name = reader.read_string(namelen).map_err(|e| state.io_error(e))?;
+                state.set_attribute(tag, name.clone());
+                let vsize = reader.read_u16::<BigEndian>().map_err(|e| state.io_error(e))? as usize;
+                state.check_token(reader.offset + vsize, namelen, vsize)?;
+                let data = reader.read_vec(vsize).map_err(|e| state.io_error(e))?;
+                state.value_tag(tag, name, &data)?;
+            } else {
+                return Err(state.error(ParseErrorKind::InvalidTag(tag)))
+            }
+        }
+
+        Ok(state.into_result(header))
+    }
+}
+
+/// Progress of the push parser
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PushStatus {
+    /// All fed data was consumed, the message is not complete yet
+    NeedMoreData,
+    /// End of attributes was reached. `consumed` bytes of the last fed slice belong to the
+    /// IPP message, the rest is document data.
+    Complete { consumed: usize },
+}
+
+/// Result of the push parser
+pub struct IppPushResult {
+    /// Parsed header and attributes
+    pub result: IppParseResult,
+    /// Offset of the document data from the start of the stream
+    pub data_offset: usize,
+}
+
+/// Resumable IPP parser which is fed with byte slices as they arrive,
+/// for use with non-blocking I/O
+pub struct IppPushParser {
+    state: ParseState,
+    header: Option<IppHeader>,
+    /// bytes of the incomplete header or tag
+    buffer: Vec<u8>,
+    /// stream offset of the first byte in buffer
+    offset: usize,
+    complete: bool,
+    /// error which stopped parsing, returned again by every later call
+    error: Option<ParseError>
+}
+
+impl Default for IppPushParser {
+    fn default() -> IppPushParser {
+        IppPushParser::new()
+    }
+}
+
+impl IppPushParser {
+    /// Create push parser
+    pub fn new() -> IppPushParser {
+        IppPushParser {
+            state: ParseState { emit: true, ..ParseState::new(ParseMode::default(), ParseLimits::default()) },
+            header: None,
+            buffer: Vec::new(),
+            offset: 0,
+            complete: false,
+            error: None
+        }
+    }
+
+    /// Set validation mode, `ParseMode::Normal` by default
+    pub fn set_mode(&mut self, mode: ParseMode) {
+        self.state.mode = mode;
+    }
+
+    /// Set resource limits, `ParseLimits::default()` by default
+    pub fn set_limits(&mut self, limits: ParseLimits) {
+        self.state.limits = limits;
+    }
+
+    /// Check whether the end of attributes was reached
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Get header once it is parsed
+    pub fn header(&self) -> Option<&IppHeader> {
+        self.header.as_ref()
+    }
+
+    /// Take attributes completed since the last call, together with their group tag.
+    /// An attribute is complete when the next attribute or group starts.
+    /// The returned attributes are copies, the attribute list returned by `finish` has all of them as well.
+    pub fn take_attributes(&mut self) -> Vec<(DelimiterTag, IppAttribute)> {
+        self.state.completed.drain(..).map(|(tag, name, value)| (tag, IppAttribute::new(&name, value))).collect()
+    }
+
+    /// Feed the next chunk of the stream. Data after the end of attributes is not consumed.
+    /// After a parse error the parser stops, every later call returns the same error.
+    pub fn feed(&mut self, data: &[u8]) -> Result<PushStatus> {
+        if let Some(ref error) = self.error {
+            return Err(IppError::ParseError(error.clone()));
+        }
+        match self.process(data) {
+            Err(IppError::ParseError(error)) => {
+                self.error = Some(error.clone());
+                Err(IppError::ParseError(error))
+            }
+            result => result
+        }
+    }
+
+    fn process(&mut self, data: &[u8]) -> Result<PushStatus> {
+        if self.complete {
+            return Ok(PushStatus::Complete { consumed: 0 });
+        }
+        let carried = self.buffer.len();
+        self.buffer.extend_from_slice(data);
+
+        let mut pos = 0;
+        while let Some(size) = self.next_token(pos)? {
+            pos += size;
+            if self.complete {
+                self.buffer.clear();
+                self.offset += pos;
+                return Ok(PushStatus::Complete { consumed: pos - carried });
+            }
+        }
+
+        self.buffer.drain(..pos);
+        self.offset += pos;
+        Ok(PushStatus::NeedMoreData)
+    }
+
+    /// process header or tag at the buffer position, returns its size or None if it is incomplete
+    fn next_token(&mut self, pos: usize) -> Result<Option<usize>> {
+        let buf = &self.buffer[pos..];
+        if self.header.is_none() {
+            if buf.len() < 8 {
+                return Ok(None);
+            }
+            let header = IppHeader::from_reader(&mut &buf[..8])?;
+            debug!("IPP reply header: {:?}", header);
+            self.header = Some(header);
+            return Ok(Some(8));
+        }
+
+        match self.state.slice_tag(self.offset + pos, buf)? {
+            Some((size, end)) => {
+                self.complete = end;
+                Ok(Some(size))
+            }
+            None => Ok(None)
+        }
+    }
+
+    /// Finish parsing after the end of attributes. In lenient mode the end of stream
+    /// at a tag boundary is accepted instead.
+    pub fn finish(mut self) -> Result<IppPushResult> {
+        if let Some(error) = self.error {
+            return Err(IppError::ParseError(error));
+        }
+        if !self.complete {
+            let tag = if self.header.is_some() { self.buffer.first().cloned() } else { None };
+            self.state.start_tag(self.offset, tag);
+            if self.header.is_none() || !self.buffer.is_empty() {
+                return Err(self.state.error(ParseErrorKind::UnexpectedEof));
+            }
+            if self.state.mode != ParseMode::Lenient {
+                return Err(self.state.error(ParseErrorKind::MissingEndOfAttributes));
+            }
+            self.state.end_of_stream()?;
+        }
+        let header = self.header.take().ok_or_else(|| self.state.error(ParseErrorKind::UnexpectedEof))?;
+        Ok(IppPushResult { result: self.state.into_result(header), data_offset: self.offset })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header() -> Vec<u8> {
+        vec![1, 1, 0, 2, 0, 0, 0, 1]
+    }
+
+    fn value(tag: ValueTag, name: &str, data: &[u8]) -> Vec<u8> {
+        let mut retval = vec![tag as u8];
+        retval.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        retval.extend_from_slice(name.as_bytes());
+        retval.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        retval.extend_from_slice(data);
+        retval
+    }
+
+    fn message(tags: &[Vec<u8>]) -> Vec<u8> {
+        let mut retval = header();
+        retval.push(DelimiterTag::OperationAttributes as u8);
+        retval.extend(value(ValueTag::Charset, ATTRIBUTES_CHARSET, b"utf-8"));
+        for tag in tags {
+            retval.extend_from_slice(tag);
+        }
+        retval
+    }
+
+    fn parse(data: &[u8], mode: ParseMode) -> Result<IppParseResult> {
+        let mut reader = data;
+        let mut parser = IppParser::new(&mut reader);
+        parser.set_mode(mode);
+        parser.parse()
+    }
+
+    fn parse_error(data: &[u8], mode: ParseMode) -> ParseError {
+        match parse(data, mode) {
+            Err(IppError::ParseError(e)) => e,
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("message was accepted")
+        }
+    }
+
+    #[test]
+    fn test_stray_member_name_has_no_attribute() {
+        let data = message(&[value(ValueTag::MemberAttrName, "", b"m"), vec![DelimiterTag::EndOfAttributes as u8]]);
+
+        let error = parse_error(&data, ParseMode::Strict);
+        assert_eq!(error.kind, ParseErrorKind::InvalidCollection);
+        assert_eq!(error.attribute, None);
+        assert_eq!(error.offset, 37);
+
+        let result = parse(&data, ParseMode::Lenient).unwrap();
+        let warning = &result.warnings()[0];
+        assert_eq!(warning.kind, ParseErrorKind::InvalidCollection);
+        assert_eq!(warning.attribute, None);
+    }
+
+    #[test]
+    fn test_invalid_tag_has_no_attribute() {
+        let data = message(&[vec![0x00]]);
+        let error = parse_error(&data, ParseMode::Normal);
+        assert_eq!(error.kind, ParseErrorKind::InvalidTag(0));
+        assert_eq!(error.attribute, None);
+        assert_eq!(error.group, Some(DelimiterTag::OperationAttributes));
+        assert_eq!(error.tag, Some(0));
+    }
+
+    #[test]
+    fn test_additional_value_belongs_to_pending_attribute() {
+        let data = message(&[value(ValueTag::Keyword, "media", b"a4"), value(ValueTag::Boolean, "", &[2])]);
+        let error = parse_error(&data, ParseMode::Strict);
+        assert_eq!(error.kind, ParseErrorKind::InvalidBoolean(2));
+        assert_eq!(error.attribute.as_deref(), Some("media"));
+    }
+
+    fn push_message() -> Vec<u8> {
+        let mut data = message(&[
+            value(ValueTag::Keyword, "requested-attributes", b"all"),
+            value(ValueTag::Keyword, "", b"media-col-database"),
+            vec![DelimiterTag::JobAttributes as u8],
+            value(ValueTag::Integer, "copies", &[0, 0, 0, 2]),
+            vec![DelimiterTag::EndOfAttributes as u8]]);
+        data.extend_from_slice(b"%PDF");
+        data
+    }
+
+    #[test]
+    fn test_push_default_emits_attributes() {
+        let mut parser = IppPushParser::default();
+        let data = push_message();
+        assert_eq!(parser.feed(&data).unwrap(), PushStatus::Complete { consumed: data.len() - 4 });
+        let names: Vec<_> = parser.take_attributes().into_iter().map(|(tag, attr)| (tag, attr.name().to_string())).collect();
+        assert_eq!(names, vec![
+            (DelimiterTag::OperationAttributes, ATTRIBUTES_CHARSET.to_string()),
+            (DelimiterTag::OperationAttributes, "requested-attributes".to_string()),
+            (DelimiterTag::JobAttributes, "copies".to_string())]);
+    }
+
+    #[test]
+    fn test_push_byte_by_byte() {
+        let data = push_message();
+        let mut parser = IppPushParser::new();
+        let mut attributes = Vec::new();
+        let mut data_offset = None;
+        for (i, byte) in data.iter().enumerate() {
+            match parser.feed(&[*byte]).unwrap() {
+                PushStatus::NeedMoreData => assert!(data_offset.is_none()),
+                PushStatus::Complete { consumed } => {
+                    if data_offset.is_none() {
+                        assert_eq!(consumed, 1);
+                        data_offset = Some(i + 1);
+                    } else {
+                        assert_eq!(consumed, 0);
                     }
-                } else if let Some(val_list) = stack.last_mut() {
-                    // add attribute to the current collection
-                    val_list.push(value);
                 }
-            } else {
-                return Err(IppError::TagError(tag))
             }
+            attributes.extend(parser.take_attributes());
+        }
+        assert_eq!(data_offset, Some(data.len() - 4));
+        assert_eq!(attributes.len(), 3);
+        assert!(parser.is_complete());
+        assert_eq!(parser.header().unwrap().request_id, 1);
+
+        let result = parser.finish().unwrap();
+        assert_eq!(result.data_offset, data.len() - 4);
+        let pulled = parse(&data, ParseMode::Normal).unwrap();
+        let mut written = Vec::new();
+        result.result.attributes().write(&mut written).unwrap();
+        let mut expected = Vec::new();
+        pulled.attributes().write(&mut expected).unwrap();
+        assert_eq!(written, expected);
+    }
+
+    #[test]
+    fn test_push_truncated() {
+        let data = push_message();
+        let mut parser = IppPushParser::new();
+        assert_eq!(parser.feed(&data[..20]).unwrap(), PushStatus::NeedMoreData);
+        match parser.finish() {
+            Err(IppError::ParseError(e)) => {
+                assert_eq!(e.kind, ParseErrorKind::UnexpectedEof);
+                assert_eq!(e.offset, 9);
+            }
+            _ => panic!("truncated message was accepted")
+        }
+    }
+
+    #[test]
+    fn test_error_context() {
+        let data = message(&[value(ValueTag::Integer, "copies", &[0, 0, 2])]);
+        let error = parse_error(&data, ParseMode::Strict);
+        assert_eq!(error, ParseError {
+            offset: 37,
+            group: Some(DelimiterTag::OperationAttributes),
+            attribute: Some("copies".to_string()),
+            tag: Some(ValueTag::Integer as u8),
+            kind: ParseErrorKind::InvalidLength { tag: ValueTag::Integer as u8, expected: 4, actual: 3 }
+        });
+        assert_eq!(error.to_string(), "value with tag 0x21 has length 3, expected 4 at offset 37 \
+                                       in operation-attributes-tag, attribute 'copies', tag 0x21");
+
+        let error = parse_error(&data[..data.len() - 2], ParseMode::Normal);
+        assert_eq!((error.kind, error.offset, error.attribute.as_deref()),
+                   (ParseErrorKind::UnexpectedEof, 37, Some("copies")));
+
+        let error = parse_error(&data[..4], ParseMode::Normal);
+        assert_eq!((error.kind, error.offset, error.group, error.tag), (ParseErrorKind::UnexpectedEof, 0, None, None));
+    }
+
+    #[test]
+    fn test_lenient_warnings() {
+        let mut data = header();
+        data.extend(value(ValueTag::Keyword, "media", b"a4"));
+        data.push(DelimiterTag::EndOfAttributes as u8);
+
+        assert_eq!(parse_error(&data, ParseMode::Strict).kind, ParseErrorKind::MissingGroup);
+        let result = parse(&data, ParseMode::Lenient).unwrap();
+        assert_eq!(result.warnings().len(), 1);
+        assert_eq!(result.warnings()[0].attribute.as_deref(), Some("media"));
+        assert!(result.attributes().get(DelimiterTag::OperationAttributes, "media").is_some());
+    }
+
+    fn limit_error(data: &[u8], limits: ParseLimits) -> ParseError {
+        let mut reader = data;
+        let mut parser = IppParser::new(&mut reader);
+        parser.set_limits(limits);
+        let pulled = match parser.parse() {
+            Err(IppError::ParseError(e)) => e,
+            _ => panic!("message was accepted")
+        };
+
+        let mut parser = IppPushParser::new();
+        parser.set_limits(limits);
+        match parser.feed(data) {
+            Err(IppError::ParseError(e)) => assert_eq!(e, pulled),
+            _ => panic!("message was accepted by the push parser")
+        }
+        pulled
+    }
+
+    #[test]
+    fn test_limits() {
+        let data = message(&[
+            value(ValueTag::Keyword, "media", b"a4"),
+            value(ValueTag::Keyword, "", b"letter"),
+            value(ValueTag::BegCollection, "media-col", b""),
+            value(ValueTag::MemberAttrName, "", b"media-size"),
+            value(ValueTag::BegCollection, "", b""),
+            value(ValueTag::EndCollection, "", b""),
+            value(ValueTag::EndCollection, "", b""),
+            vec![DelimiterTag::EndOfAttributes as u8]]);
+        assert!(parse(&data, ParseMode::Strict).is_ok());
+
+        let value_too_long = StatusCode::ClientErrorRequestValueTooLong as u16;
+        let entity_too_long = StatusCode::ClientErrorRequestEntityTooLong as u16;
+        let defaults = ParseLimits::default();
+        let cases = [
+            (ParseLimits { max_name_length: 17, ..defaults },
+             ParseErrorKind::NameTooLong { length: ATTRIBUTES_CHARSET.len(), limit: 17 }, value_too_long),
+            (ParseLimits { max_value_length: 1, ..defaults },
+             ParseErrorKind::ValueTooLong { length: 5, limit: 1 }, value_too_long),
+            (ParseLimits { max_message_size: 40, ..defaults },
+             ParseErrorKind::MessageTooLarge { limit: 40 }, entity_too_long),
+            (ParseLimits { max_attributes: 1, ..defaults },
+             ParseErrorKind::TooManyAttributes { limit: 1 }, entity_too_long),
+            (ParseLimits { max_values: 1, ..defaults },
+             ParseErrorKind::TooManyValues { limit: 1 }, entity_too_long),
+            (ParseLimits { max_depth: 1, ..defaults },
+             ParseErrorKind::NestingTooDeep { limit: 1 }, entity_too_long)];
+        for (limits, kind, status) in cases.iter().cloned() {
+            let error = limit_error(&data, limits);
+            assert_eq!(error.kind, kind);
+            assert_eq!(error.kind.status_code() as u16, status, "status of {}", kind);
+        }
+    }
+
+    #[test]
+    fn test_unlimited() {
+        let mut tags = vec![value(ValueTag::BegCollection, "media-col-database", b"")];
+        for _ in 0..ParseLimits::default().max_attributes {
+            tags.push(value(ValueTag::MemberAttrName, "", b"media-type"));
+            tags.push(value(ValueTag::Keyword, "", b"stationery"));
+        }
+        tags.push(value(ValueTag::EndCollection, "", b""));
+        tags.push(vec![DelimiterTag::EndOfAttributes as u8]);
+        let data = message(&tags);
+
+        assert_eq!(limit_error(&data, ParseLimits::default()).kind,
+                   ParseErrorKind::TooManyAttributes { limit: ParseLimits::default().max_attributes });
+        let mut reader = &data[..];
+        let mut parser = IppParser::new(&mut reader);
+        parser.set_limits(ParseLimits::unlimited());
+        assert!(parser.parse().is_ok());
+    }
+
+    #[test]
+    fn test_duplicate_attributes_are_kept() {
+        let data = message(&[
+            value(ValueTag::Keyword, "media", b"a4"),
+            value(ValueTag::Integer, "copies", &[0, 0, 0, 1]),
+            value(ValueTag::Keyword, "media", b"letter"),
+            vec![DelimiterTag::EndOfAttributes as u8]]);
+
+        assert_eq!(parse_error(&data, ParseMode::Strict).kind, ParseErrorKind::DuplicateAttribute);
+
+        let result = parse(&data, ParseMode::Normal).unwrap();
+        let names: Vec<_> = result.attributes().groups()[0].iter().map(|attr| attr.name()).collect();
+        assert_eq!(names, [ATTRIBUTES_CHARSET, "media", "copies", "media"]);
+        let media = result.attributes().get(DelimiterTag::OperationAttributes, "media").unwrap();
+        assert!(matches!(*media.value(), IppValue::Keyword(ref media) if media == "a4"));
+        let mut written = header();
+        result.attributes().write(&mut written).unwrap();
+        assert_eq!(written, data);
+
+        let result = parse(&data, ParseMode::Lenient).unwrap();
+        assert_eq!(result.warnings()[0].kind, ParseErrorKind::DuplicateAttribute);
+        assert_eq!(result.attributes().groups()[0].len(), 4);
+    }
+
+    #[test]
+    fn test_push_error_is_kept() {
+        let mut data = message(&[value(ValueTag::Keyword, "media", b"a4"), vec![0x00]]);
+        let error = parse_error(&data, ParseMode::Normal);
+        data.extend(value(ValueTag::Keyword, "sides", b"one-sided"));
+
+        let mut parser = IppPushParser::new();
+        let check = |result: Result<PushStatus>| match result {
+            Err(IppError::ParseError(e)) => assert_eq!(e, error),
+            _ => panic!("parse error is not reported")
+        };
+        check(parser.feed(&data));
+        assert_eq!(parser.take_attributes().len(), 1);
+        check(parser.feed(&data));
+        check(parser.feed(&[DelimiterTag::EndOfAttributes as u8]));
+        assert!(parser.take_attributes().is_empty());
+        match parser.finish() {
+            Err(IppError::ParseError(e)) => assert_eq!(e, error),
+            _ => panic!("parse error is not reported by finish")
         }
+    }
 
-        Ok(IppParseResult::new(header, retval))
+    #[test]
+    fn test_strict_rejections() {
+        let datetime = [0x07, 0xea, 13, 1, 0, 0, 0, 0, b'+', 0, 0];
+        let cases = [
+            (value(ValueTag::Integer, "copies", &[0, 0, 2]),
+             ParseErrorKind::InvalidLength { tag: ValueTag::Integer as u8, expected: 4, actual: 3 }),
+            (value(ValueTag::Boolean, "color-supported", &[2]), ParseErrorKind::InvalidBoolean(2)),
+            (value(ValueTag::DateTime, "printer-current-time", &datetime), ParseErrorKind::InvalidDateTime),
+            ([vec![DelimiterTag::JobAttributes as u8], value(ValueTag::Keyword, "", b"a4")].concat(),
+             ParseErrorKind::MissingAttributeName)];
+        for (tag, kind) in cases.iter() {
+            let data = message(&[tag.clone(), vec![DelimiterTag::EndOfAttributes as u8]]);
+            assert_eq!(parse_error(&data, ParseMode::Strict).kind, *kind);
+            let result = parse(&data, ParseMode::Lenient).unwrap();
+            assert_eq!(result.warnings()[0].kind, *kind);
+            assert!(parse(&data, ParseMode::Normal).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_group_order() {
+        let group = |tag: DelimiterTag| vec![tag as u8, ValueTag::NoValue as u8, 0, 1, b'x', 0, 0];
+        let order = |tags: &[DelimiterTag]| {
+            let mut data = header();
+            for tag in tags {
+                data.extend(group(*tag));
+            }
+            data.push(DelimiterTag::EndOfAttributes as u8);
+            parse(&data, ParseMode::Strict).map(|_| ())
+        };
+        let (operation, unsupported) = (DelimiterTag::OperationAttributes, DelimiterTag::UnsupportedAttributes);
+        let (job, printer) = (DelimiterTag::JobAttributes, DelimiterTag::PrinterAttributes);
+
+        assert!(order(&[operation, unsupported, job, job, printer]).is_ok());
+        assert!(order(&[operation, printer, job]).is_ok());
+        for (tags, tag) in [(&[job, operation][..], job), (&[operation, job, operation][..], operation),
+                            (&[operation, job, unsupported][..], unsupported),
+                            (&[operation, unsupported, unsupported][..], unsupported)].iter() {
+            match order(tags) {
+                Err(IppError::ParseError(e)) => assert_eq!(e.kind, ParseErrorKind::GroupOrder(*tag)),
+                _ => panic!("group order {:?} was accepted", tags)
+            }
+        }
     }
 }
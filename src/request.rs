@@ -1,28 +1,36 @@
 //!
 //! IPP request
 //!
+use std::borrow::Cow;
 use std::io::{self, Read, Write};
 
 use attribute::{IppAttribute, IppAttributeList};
 use ::{Result, IPP_VERSION, IppHeader};
 use consts::tag::DelimiterTag;
 use consts::operation::Operation;
-use consts::attribute::{PRINTER_URI, ATTRIBUTES_CHARSET, ATTRIBUTES_NATURAL_LANGUAGE};
+use consts::attribute::{ATTRIBUTES_CHARSET, ATTRIBUTES_NATURAL_LANGUAGE};
 use value::IppValue;
+use charset::IppCharset;
+use document::IppDocument;
+use target::IppTarget;
 use parser::IppParser;
 
 /// IPP request struct
 pub struct IppRequestResponse<'a> {
     /// Operation ID
     header: IppHeader,
-    /// IPP attributes
-    attributes: IppAttributeList,
+    /// IPP attributes, borrowed when an owned request is sent
+    attributes: Cow<'a, IppAttributeList>,
     /// Optional payload to send after IPP-encoded stream (for example Print-Job operation)
-    payload: Option<&'a mut Read>
+    payload: Option<&'a mut dyn Read>,
+    /// Check attributes against the RFC 8011 maximum lengths before writing
+    check_limits: bool
 }
 
 pub trait IppRequestTrait {
     fn header(&self) -> &IppHeader;
+    fn attributes(&self) -> &IppAttributeList;
+    fn payload(&mut self) -> Option<&mut dyn Read>;
 }
 
 impl<'a> IppRequestTrait for IppRequestResponse<'a> {
@@ -30,31 +38,43 @@ impl<'a> IppRequestTrait for IppRequestResponse<'a> {
     fn header(&self) -> &IppHeader {
         &self.header
     }
+
+    /// Get attributes
+    fn attributes(&self) -> &IppAttributeList {
+        &self.attributes
+    }
+
+    /// Get document data which follows the attributes
+    fn payload(&mut self) -> Option<&mut dyn Read> {
+        match self.payload {
+            Some(ref mut payload) => Some(&mut **payload),
+            None => None
+        }
+    }
 }
 
 impl<'a> IppRequestResponse<'a> {
-    /// Create new IPP request for the operation and uri
+    /// Create new IPP request for the operation and printer uri
     pub fn new(operation: Operation, uri: &str) -> IppRequestResponse<'a> {
+        IppRequestResponse::new_with_target(operation, &IppTarget::Printer(uri.to_string()))
+    }
+
+    /// Create new IPP request for the operation and target
+    pub fn new_with_target(operation: Operation, target: &IppTarget) -> IppRequestResponse<'a> {
 
         let hdr = IppHeader::new(IPP_VERSION, operation as u16, 1);
         let mut retval = IppRequestResponse {
             header: hdr,
-            attributes: IppAttributeList::new(),
-            payload: None };
+            attributes: Cow::Owned(IppAttributeList::new()),
+            payload: None,
+            check_limits: false };
 
-        retval.set_attribute(
-            DelimiterTag::OperationAttributes,
-            IppAttribute::new(ATTRIBUTES_CHARSET,
-                              IppValue::Charset("utf-8".to_string())));
-        retval.set_attribute(
-            DelimiterTag::OperationAttributes,
-            IppAttribute::new(ATTRIBUTES_NATURAL_LANGUAGE,
-                              IppValue::NaturalLanguage("en".to_string())));
+        retval.set_charset(IppCharset::Utf8);
+        retval.set_natural_language("en");
 
-        retval.set_attribute(
-            DelimiterTag::OperationAttributes,
-            IppAttribute::new(PRINTER_URI,
-                              IppValue::Uri(uri.replace("http", "ipp").to_string())));
+        for attribute in target.attributes() {
+            retval.set_attribute(DelimiterTag::OperationAttributes, attribute);
+        }
 
         retval
 
@@ -64,29 +84,30 @@ impl<'a> IppRequestResponse<'a> {
         let hdr = IppHeader::new(IPP_VERSION, status, id);
         let mut retval = IppRequestResponse {
             header: hdr,
-            attributes: IppAttributeList::new(),
-            payload: None };
+            attributes: Cow::Owned(IppAttributeList::new()),
+            payload: None,
+            check_limits: false };
 
-        retval.set_attribute(
-            DelimiterTag::OperationAttributes,
-            IppAttribute::new(ATTRIBUTES_CHARSET,
-                              IppValue::Charset("utf-8".to_string())));
-        retval.set_attribute(
-            DelimiterTag::OperationAttributes,
-            IppAttribute::new(ATTRIBUTES_NATURAL_LANGUAGE,
-                              IppValue::NaturalLanguage("en".to_string())));
+        retval.set_charset(IppCharset::Utf8);
+        retval.set_natural_language("en");
 
         retval
     }
 
-    /// Create IppRequestResponse from the parser
-    pub fn from_parser<'b>(parser: &mut IppParser) -> Result<IppRequestResponse<'b>> {
-        let res = parser.parse()?;
+    /// Create IppRequestResponse from the header and attributes, without payload
+    pub fn from_parts(header: IppHeader, attributes: IppAttributeList) -> IppRequestResponse<'a> {
+        IppRequestResponse { header, attributes: Cow::Owned(attributes), payload: None, check_limits: false }
+    }
+
+    /// Create IppRequestResponse from the parser, the rest of the stream becomes the payload
+    pub fn from_parser(mut parser: IppParser<'a>) -> Result<IppRequestResponse<'a>> {
+        let (header, attributes) = parser.parse()?.into_parts();
 
         Ok(IppRequestResponse {
-            header: res.header().clone(),
-            attributes: res.attributes().clone(),
-            payload: None,
+            header,
+            attributes: Cow::Owned(attributes),
+            payload: Some(parser.into_reader()),
+            check_limits: false
         })
     }
 
@@ -94,23 +115,52 @@ impl<'a> IppRequestResponse<'a> {
         &mut self.header
     }
 
-    /// Get attributes
-    pub fn attributes(&self) -> &IppAttributeList {
-        &self.attributes
-    }
-
     /// Set payload
-    pub fn set_payload(&mut self, payload: &'a mut Read) {
+    pub fn set_payload(&mut self, payload: &'a mut dyn Read) {
         self.payload = Some(payload)
     }
 
+    /// Reject attributes which exceed the RFC 8011 maximum lengths of their syntaxes
+    /// when the request is written, disabled by default
+    pub fn set_check_limits(&mut self, check_limits: bool) {
+        self.check_limits = check_limits;
+    }
+
+    /// Set charset of text and name values, sent as `attributes-charset`
+    pub fn set_charset(&mut self, charset: IppCharset) {
+        self.set_attribute(
+            DelimiterTag::OperationAttributes,
+            IppAttribute::new(ATTRIBUTES_CHARSET, IppValue::Charset(charset.name().to_string())));
+    }
+
+    /// Set natural language of text and name values, sent as `attributes-natural-language`
+    pub fn set_natural_language(&mut self, language: &str) {
+        self.set_attribute(
+            DelimiterTag::OperationAttributes,
+            IppAttribute::new(ATTRIBUTES_NATURAL_LANGUAGE, IppValue::NaturalLanguage(language.to_string())));
+    }
+
+    /// Set attribute, borrowed attributes are copied first
     pub fn set_attribute(&mut self, group: DelimiterTag, attribute: IppAttribute) {
-        /// Set attribute
-        self.attributes.add(group, attribute);
+        self.attributes.to_mut().add(group, attribute);
+    }
+
+    /// Check that the attributes can be written: they must fit into the wire format and,
+    /// when enabled, into the RFC 8011 maximum lengths
+    pub fn check(&self) -> Result<()> {
+        self.attributes.check_encodable()?;
+        if self.check_limits {
+            self.attributes.check_limits()?;
+        }
+        Ok(())
     }
 
-    /// Serialize request into the binary stream (TCP)
-    pub fn write(&'a mut self, writer: &mut Write) -> Result<usize> {
+    /// Serialize request into the binary stream (TCP).
+    /// Nothing is written if any attribute does not fit into the wire format or, when enabled,
+    /// exceeds the RFC 8011 maximum lengths.
+    pub fn write(&'a mut self, writer: &mut dyn Write) -> Result<usize> {
+        self.check()?;
+
         let mut retval = self.header.write(writer)?;
 
         retval += self.attributes.write(writer)?;
@@ -126,3 +176,163 @@ impl<'a> IppRequestResponse<'a> {
         Ok(retval)
     }
 }
+
+/// IPP request which owns its document. It can be stored in a queue, sent to another
+/// thread and resent, the document is rewound before every write.
+pub struct IppOwnedRequest {
+    header: IppHeader,
+    attributes: IppAttributeList,
+    document: Option<IppDocument>
+}
+
+impl IppRequestTrait for IppOwnedRequest {
+    /// Get header
+    fn header(&self) -> &IppHeader {
+        &self.header
+    }
+
+    /// Get attributes
+    fn attributes(&self) -> &IppAttributeList {
+        &self.attributes
+    }
+
+    /// Get document
+    fn payload(&mut self) -> Option<&mut dyn Read> {
+        self.document.as_mut().map(|document| document as &mut dyn Read)
+    }
+}
+
+impl IppOwnedRequest {
+    /// Create new IPP request for the operation and printer uri
+    pub fn new(operation: Operation, uri: &str) -> IppOwnedRequest {
+        IppOwnedRequest::new_with_target(operation, &IppTarget::Printer(uri.to_string()))
+    }
+
+    /// Create new IPP request for the operation and target
+    pub fn new_with_target(operation: Operation, target: &IppTarget) -> IppOwnedRequest {
+        let request = IppRequestResponse::new_with_target(operation, target);
+        IppOwnedRequest::from_parts(request.header, request.attributes.into_owned())
+    }
+
+    /// Create IppOwnedRequest from the header and attributes, without document
+    pub fn from_parts(header: IppHeader, attributes: IppAttributeList) -> IppOwnedRequest {
+        IppOwnedRequest { header, attributes, document: None }
+    }
+
+    pub fn header_mut(&mut self) -> &mut IppHeader {
+        &mut self.header
+    }
+
+    /// Set document
+    pub fn set_document(&mut self, document: IppDocument) {
+        self.document = Some(document)
+    }
+
+    /// Take document out of the request
+    pub fn take_document(&mut self) -> Option<IppDocument> {
+        self.document.take()
+    }
+
+    /// Set attribute
+    pub fn set_attribute(&mut self, group: DelimiterTag, attribute: IppAttribute) {
+        self.attributes.add(group, attribute);
+    }
+
+    /// Rewind the document and borrow the request for sending, the attributes are not copied
+    pub fn to_request_response(&mut self) -> Result<IppRequestResponse<'_>> {
+        let payload = match self.document {
+            Some(ref mut document) => {
+                document.rewind()?;
+                Some(document as &mut dyn Read)
+            }
+            None => None
+        };
+        Ok(IppRequestResponse {
+            header: self.header.clone(),
+            attributes: Cow::Borrowed(&self.attributes),
+            payload,
+            check_limits: false
+        })
+    }
+
+    /// Serialize request into the binary stream (TCP), the document is sent from the start
+    pub fn write(&mut self, writer: &mut dyn Write) -> Result<usize> {
+        self.to_request_response()?.write(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::IppError;
+
+    fn request<'a>(attribute: IppAttribute) -> IppRequestResponse<'a> {
+        let mut retval = IppRequestResponse::new(Operation::PrintJob, "ipp://localhost/printer");
+        retval.set_attribute(DelimiterTag::JobAttributes, attribute);
+        retval
+    }
+
+    #[test]
+    fn test_no_partial_write() {
+        let mut data = Vec::new();
+        let mut oversized = request(IppAttribute::new("job-name", IppValue::NameWithoutLanguage("x".repeat(0x10000))));
+        assert!(matches!(oversized.write(&mut data), Err(IppError::LengthError { length: 0x10000, .. })));
+        assert!(data.is_empty());
+
+        let mut document = &b"%PDF"[..];
+        let mut owned = IppOwnedRequest::new(Operation::PrintJob, "ipp://localhost/printer");
+        owned.set_attribute(DelimiterTag::JobAttributes, IppAttribute::new("job-name", IppValue::NameWithoutLanguage("x".repeat(0x10000))));
+        owned.set_document(IppDocument::from_bytes(document.to_vec()));
+        assert!(owned.write(&mut data).is_err());
+        assert!(data.is_empty());
+
+        let mut request = request(IppAttribute::new("job-name", IppValue::NameWithoutLanguage("x".repeat(256))));
+        request.set_payload(&mut document);
+        request.set_check_limits(true);
+        assert!(matches!(request.check(), Err(IppError::LengthError { length: 256, limit: 255, .. })));
+        assert!(request.write(&mut data).is_err());
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn test_new_with_target() {
+        // only the scheme is mapped, "http" elsewhere in the URI is kept
+        let request = IppRequestResponse::new(Operation::GetPrinterAttributes, "http://httpd.local:631/printers/http");
+        let operation = request.attributes().get_group(DelimiterTag::OperationAttributes).unwrap();
+        let names: Vec<&str> = operation.iter().map(|attr| attr.name()).collect();
+        assert_eq!(names, [ATTRIBUTES_CHARSET, ATTRIBUTES_NATURAL_LANGUAGE, "printer-uri"]);
+        match *operation.get("printer-uri").unwrap().value() {
+            IppValue::Uri(ref uri) => assert_eq!(uri, "ipp://httpd.local:631/printers/http"),
+            ref value => panic!("unexpected value {:?}", value)
+        }
+
+        let target = IppTarget::PrinterJob("ipp://localhost/printers/test".to_string(), 3);
+        let mut request = IppRequestResponse::new_with_target(Operation::CancelJob, &target);
+        request.set_attribute(DelimiterTag::OperationAttributes,
+                              IppAttribute::new("requesting-user-name", IppValue::NameWithoutLanguage("user".to_string())));
+        let operation = request.attributes().get_group(DelimiterTag::OperationAttributes).unwrap();
+        let names: Vec<&str> = operation.iter().map(|attr| attr.name()).collect();
+        assert_eq!(names, [ATTRIBUTES_CHARSET, ATTRIBUTES_NATURAL_LANGUAGE, "printer-uri", "job-id", "requesting-user-name"]);
+    }
+
+    #[test]
+    fn test_owned_request_is_borrowed() {
+        let mut owned = IppOwnedRequest::new(Operation::PrintJob, "ipp://localhost/printer");
+        owned.set_document(IppDocument::from_bytes(&b"%PDF"[..]));
+        let attributes: *const IppAttributeList = owned.attributes();
+
+        let mut expected = Vec::new();
+        for _ in 0..2 {
+            let mut request = owned.to_request_response().unwrap();
+            assert!(::std::ptr::eq(request.attributes(), attributes));
+            let mut data = Vec::new();
+            request.write(&mut data).unwrap();
+            assert!(data.ends_with(b"%PDF"));
+            if expected.is_empty() {
+                expected = data;
+            } else {
+                assert_eq!(data, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,915 @@
+//!
+//! PWG Semantic Model (PWG 5108.01) XML representation, enabled by the `xml` feature
+//!
+//! Job attributes are grouped into `JobDescription`, `JobProcessing` and `JobStatus`
+//! elements, document attributes into `DocumentProcessing` and printer attributes into
+//! `PrinterDescription` and `PrinterStatus`. A `JobTicket` has the description and processing
+//! elements of a job, a `Job` adds the status. Element names are derived from the IPP names,
+//! `print-color-mode` becomes `PrintColorMode`.
+//!
+//! Values are written as element text and collections as child elements. A 1setOf is an
+//! element with one child per value, named without the `Supported` or plural `s` suffix:
+//! `media-supported` becomes `MediaSupported` with `Media` children. Attributes known to be
+//! 1setOf keep that shape with a single value. rangeOfInteger has `Lower` and `Upper`
+//! children, resolution has `CrossFeedDirResolution`, `FeedDirResolution` and `Units`
+//! children and language-tagged strings carry `xml:lang`. Enums are written as keyword
+//! names, `job-state` 5 becomes `Processing`. Out-of-band values have no Semantic Model
+//! representation and are left out.
+//!
+//! Known attributes are read with their syntax, so that export followed by import gives back
+//! the same values. The syntax of other attributes is inferred: integer, boolean, dateTime,
+//! uri, rangeOfInteger, resolution, textWithLanguage, collection or keyword.
+//!
+use std::fmt::Write;
+use std::slice;
+
+use xml::reader::{EventReader, XmlEvent};
+
+use ::{Result, IppError};
+use attribute::{IppAttribute, IppAttributeGroup, IppAttributeList};
+use consts::tag::{DelimiterTag, ValueTag};
+use datetime::IppDateTime;
+use parser::ParseLimits;
+use value::IppValue;
+
+/// XML namespace of the PWG Semantic Model schema
+pub const SM_NAMESPACE: &str = "http://www.pwg.org/schemas/2010/12/sm";
+
+// job attributes which belong to JobDescription
+const JOB_DESCRIPTION: [&str; 10] = [
+    "job-name",
+    "job-originating-user-name",
+    "job-originating-user-uri",
+    "job-message-from-operator",
+    "job-message-to-operator",
+    "job-account-id",
+    "job-accounting-user-id",
+    "job-password",
+    "job-password-encryption",
+    "job-sheet-message"];
+
+// job attributes which belong to JobStatus, together with the time-at-* and date-time-at-* ones
+const JOB_STATUS: [&str; 20] = [
+    "attributes-charset",
+    "attributes-natural-language",
+    "job-id",
+    "job-uri",
+    "job-uuid",
+    "job-printer-uri",
+    "job-printer-up-time",
+    "job-more-info",
+    "job-state",
+    "job-state-reasons",
+    "job-state-message",
+    "job-detailed-status-messages",
+    "job-document-access-errors",
+    "job-impressions-completed",
+    "job-media-sheets-completed",
+    "job-pages-completed",
+    "job-k-octets-processed",
+    "number-of-documents",
+    "number-of-intervening-jobs",
+    "output-device-assigned"];
+
+// printer attributes which belong to PrinterStatus, all others go to PrinterDescription
+const PRINTER_STATUS: [&str; 18] = [
+    "printer-state",
+    "printer-state-reasons",
+    "printer-state-message",
+    "printer-state-change-time",
+    "printer-state-change-date-time",
+    "printer-config-change-time",
+    "printer-config-change-date-time",
+    "printer-is-accepting-jobs",
+    "printer-up-time",
+    "printer-current-time",
+    "printer-alert",
+    "printer-alert-description",
+    "printer-detailed-status-messages",
+    "printer-supply",
+    "printer-input-tray",
+    "printer-output-tray",
+    "printer-finisher",
+    "queued-job-count"];
+
+// syntax of the known job, document and printer attributes and collection members,
+// and whether they are 1setOf
+const SYNTAXES: [(&str, ValueTag, bool); 122] = [
+    ("attributes-charset", ValueTag::Charset, false),
+    ("attributes-natural-language", ValueTag::NaturalLanguage, false),
+    ("charset-configured", ValueTag::Charset, false),
+    ("charset-supported", ValueTag::Charset, true),
+    ("color-supported", ValueTag::Boolean, false),
+    ("compression", ValueTag::Keyword, false),
+    ("compression-supported", ValueTag::Keyword, true),
+    ("copies", ValueTag::Integer, false),
+    ("copies-default", ValueTag::Integer, false),
+    ("copies-supported", ValueTag::RangeOfInteger, false),
+    ("date-time-at-completed", ValueTag::DateTime, false),
+    ("date-time-at-creation", ValueTag::DateTime, false),
+    ("date-time-at-processing", ValueTag::DateTime, false),
+    ("document-format", ValueTag::MimeMediaType, false),
+    ("document-format-default", ValueTag::MimeMediaType, false),
+    ("document-format-supported", ValueTag::MimeMediaType, true),
+    ("document-name", ValueTag::NameWithoutLanguage, false),
+    ("finishings", ValueTag::Enum, true),
+    ("finishings-default", ValueTag::Enum, true),
+    ("finishings-supported", ValueTag::Enum, true),
+    ("generated-natural-language-supported", ValueTag::NaturalLanguage, true),
+    ("ipp-versions-supported", ValueTag::Keyword, true),
+    ("job-account-id", ValueTag::NameWithoutLanguage, false),
+    ("job-accounting-user-id", ValueTag::NameWithoutLanguage, false),
+    ("job-detailed-status-messages", ValueTag::TextWithoutLanguage, true),
+    ("job-document-access-errors", ValueTag::TextWithoutLanguage, true),
+    ("job-hold-until", ValueTag::Keyword, false),
+    ("job-hold-until-default", ValueTag::Keyword, false),
+    ("job-hold-until-supported", ValueTag::Keyword, true),
+    ("job-id", ValueTag::Integer, false),
+    ("job-impressions-completed", ValueTag::Integer, false),
+    ("job-k-octets-processed", ValueTag::Integer, false),
+    ("job-media-sheets-completed", ValueTag::Integer, false),
+    ("job-message-from-operator", ValueTag::TextWithoutLanguage, false),
+    ("job-message-to-operator", ValueTag::TextWithoutLanguage, false),
+    ("job-more-info", ValueTag::Uri, false),
+    ("job-name", ValueTag::NameWithoutLanguage, false),
+    ("job-originating-user-name", ValueTag::NameWithoutLanguage, false),
+    ("job-originating-user-uri", ValueTag::Uri, false),
+    ("job-pages-completed", ValueTag::Integer, false),
+    ("job-printer-up-time", ValueTag::Integer, false),
+    ("job-printer-uri", ValueTag::Uri, false),
+    ("job-priority", ValueTag::Integer, false),
+    ("job-priority-default", ValueTag::Integer, false),
+    ("job-priority-supported", ValueTag::Integer, false),
+    ("job-sheet-message", ValueTag::TextWithoutLanguage, false),
+    ("job-sheets", ValueTag::Keyword, false),
+    ("job-sheets-default", ValueTag::Keyword, false),
+    ("job-sheets-supported", ValueTag::Keyword, true),
+    ("job-state", ValueTag::Enum, false),
+    ("job-state-message", ValueTag::TextWithoutLanguage, false),
+    ("job-state-reasons", ValueTag::Keyword, true),
+    ("job-uri", ValueTag::Uri, false),
+    ("job-uuid", ValueTag::Uri, false),
+    ("media", ValueTag::Keyword, false),
+    ("media-col", ValueTag::BegCollection, false),
+    ("media-col-default", ValueTag::BegCollection, false),
+    ("media-col-ready", ValueTag::BegCollection, true),
+    ("media-color", ValueTag::Keyword, false),
+    ("media-default", ValueTag::Keyword, false),
+    ("media-ready", ValueTag::Keyword, true),
+    ("media-size", ValueTag::BegCollection, false),
+    ("media-source", ValueTag::Keyword, false),
+    ("media-source-supported", ValueTag::Keyword, true),
+    ("media-supported", ValueTag::Keyword, true),
+    ("media-type", ValueTag::Keyword, false),
+    ("media-type-supported", ValueTag::Keyword, true),
+    ("multiple-document-handling", ValueTag::Keyword, false),
+    ("multiple-document-handling-default", ValueTag::Keyword, false),
+    ("multiple-document-handling-supported", ValueTag::Keyword, true),
+    ("natural-language-configured", ValueTag::NaturalLanguage, false),
+    ("number-of-documents", ValueTag::Integer, false),
+    ("number-of-intervening-jobs", ValueTag::Integer, false),
+    ("number-up", ValueTag::Integer, false),
+    ("number-up-default", ValueTag::Integer, false),
+    ("number-up-supported", ValueTag::Integer, true),
+    ("operations-supported", ValueTag::Enum, true),
+    ("orientation-requested", ValueTag::Enum, false),
+    ("orientation-requested-default", ValueTag::Enum, false),
+    ("orientation-requested-supported", ValueTag::Enum, true),
+    ("output-bin", ValueTag::Keyword, false),
+    ("output-bin-default", ValueTag::Keyword, false),
+    ("output-bin-supported", ValueTag::Keyword, true),
+    ("output-device-assigned", ValueTag::NameWithoutLanguage, false),
+    ("page-ranges", ValueTag::RangeOfInteger, true),
+    ("page-ranges-supported", ValueTag::Boolean, false),
+    ("pages-per-minute", ValueTag::Integer, false),
+    ("pdl-override-supported", ValueTag::Keyword, false),
+    ("print-color-mode", ValueTag::Keyword, false),
+    ("print-color-mode-default", ValueTag::Keyword, false),
+    ("print-color-mode-supported", ValueTag::Keyword, true),
+    ("print-quality", ValueTag::Enum, false),
+    ("print-quality-default", ValueTag::Enum, false),
+    ("print-quality-supported", ValueTag::Enum, true),
+    ("printer-alert", ValueTag::OctectStringUnspecified, true),
+    ("printer-alert-description", ValueTag::TextWithoutLanguage, true),
+    ("printer-config-change-date-time", ValueTag::DateTime, false),
+    ("printer-config-change-time", ValueTag::Integer, false),
+    ("printer-current-time", ValueTag::DateTime, false),
+    ("printer-detailed-status-messages", ValueTag::TextWithoutLanguage, true),
+    ("printer-info", ValueTag::TextWithoutLanguage, false),
+    ("printer-is-accepting-jobs", ValueTag::Boolean, false),
+    ("printer-location", ValueTag::TextWithoutLanguage, false),
+    ("printer-make-and-model", ValueTag::TextWithoutLanguage, false),
+    ("printer-more-info", ValueTag::Uri, false),
+    ("printer-name", ValueTag::NameWithoutLanguage, false),
+    ("printer-resolution", ValueTag::Resolution, false),
+    ("printer-resolution-default", ValueTag::Resolution, false),
+    ("printer-resolution-supported", ValueTag::Resolution, true),
+    ("printer-state", ValueTag::Enum, false),
+    ("printer-state-message", ValueTag::TextWithoutLanguage, false),
+    ("printer-state-reasons", ValueTag::Keyword, true),
+    ("printer-up-time", ValueTag::Integer, false),
+    ("printer-uri-supported", ValueTag::Uri, true),
+    ("queued-job-count", ValueTag::Integer, false),
+    ("sides", ValueTag::Keyword, false),
+    ("sides-default", ValueTag::Keyword, false),
+    ("sides-supported", ValueTag::Keyword, true),
+    ("uri-authentication-supported", ValueTag::Keyword, true),
+    ("uri-security-supported", ValueTag::Keyword, true),
+    ("x-dimension", ValueTag::Integer, false),
+    ("y-dimension", ValueTag::Integer, false)];
+
+// keyword names of enum values, shared by the -default and -supported attributes
+const ENUM_NAMES: [(&str, &[(i32, &str)]); 6] = [
+    ("finishings", &[
+        (3, "none"), (4, "staple"), (5, "punch"), (6, "cover"), (7, "bind"), (8, "saddle-stitch"),
+        (9, "edge-stitch"), (10, "fold"), (11, "trim"), (12, "bale"), (13, "booklet-maker"),
+        (14, "jog-offset"), (15, "coat"), (16, "laminate"), (20, "staple-top-left"),
+        (21, "staple-bottom-left"), (22, "staple-top-right"), (23, "staple-bottom-right"),
+        (24, "edge-stitch-left"), (25, "edge-stitch-top"), (26, "edge-stitch-right"),
+        (27, "edge-stitch-bottom"), (28, "staple-dual-left"), (29, "staple-dual-top"),
+        (30, "staple-dual-right"), (31, "staple-dual-bottom")]),
+    ("job-state", &[
+        (3, "pending"), (4, "pending-held"), (5, "processing"), (6, "processing-stopped"),
+        (7, "canceled"), (8, "aborted"), (9, "completed")]),
+    ("operations", &[
+        (2, "print-job"), (3, "print-uri"), (4, "validate-job"), (5, "create-job"), (6, "send-document"),
+        (7, "send-uri"), (8, "cancel-job"), (9, "get-job-attributes"), (10, "get-jobs"),
+        (11, "get-printer-attributes"), (12, "hold-job"), (13, "release-job"), (14, "restart-job"),
+        (16, "pause-printer"), (17, "resume-printer"), (18, "purge-jobs")]),
+    ("orientation-requested", &[
+        (3, "portrait"), (4, "landscape"), (5, "reverse-landscape"), (6, "reverse-portrait"), (7, "none")]),
+    ("print-quality", &[(3, "draft"), (4, "normal"), (5, "high")]),
+    ("printer-state", &[(3, "idle"), (4, "processing"), (5, "stopped")])];
+
+fn known_syntax(name: &str) -> Option<(ValueTag, bool)> {
+    SYNTAXES.iter().find(|&&(known, ..)| known == name).map(|&(_, tag, set)| (tag, set))
+}
+
+fn is_set(name: &str) -> bool {
+    known_syntax(name).is_some_and(|(_, set)| set)
+}
+
+fn enum_names(name: &str) -> &'static [(i32, &'static str)] {
+    let name = name.strip_suffix("-default").or_else(|| name.strip_suffix("-supported")).unwrap_or(name);
+    ENUM_NAMES.iter().find(|&&(known, _)| known == name).map_or(&[], |&(_, names)| names)
+}
+
+fn is_job_status(attr: &IppAttribute) -> bool {
+    let name = attr.name();
+    JOB_STATUS.contains(&name) || name.starts_with("time-at-") || name.starts_with("date-time-at-")
+}
+
+fn is_job_description(attr: &IppAttribute) -> bool {
+    JOB_DESCRIPTION.contains(&attr.name())
+}
+
+fn is_job_processing(attr: &IppAttribute) -> bool {
+    !is_job_status(attr) && !is_job_description(attr)
+}
+
+fn is_printer_status(attr: &IppAttribute) -> bool {
+    PRINTER_STATUS.contains(&attr.name())
+}
+
+/// Transform IPP name into Semantic Model element name: `print-color-mode` → `PrintColorMode`
+pub fn sm_name(ipp_name: &str) -> String {
+    let mut retval = String::with_capacity(ipp_name.len());
+    for word in ipp_name.split('-') {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            retval.extend(first.to_uppercase());
+            retval.extend(chars);
+        }
+    }
+    retval
+}
+
+// name of the value elements of a 1setOf: MediaSupported → Media, Finishings → Finishing
+fn item_name(sm_name: &str) -> &str {
+    match sm_name.strip_suffix("Supported").or_else(|| sm_name.strip_suffix('s')) {
+        Some(name) if !name.is_empty() => name,
+        _ => sm_name
+    }
+}
+
+/// Transform Semantic Model element name into IPP name: `PrintColorMode` → `print-color-mode`
+pub fn ipp_name(sm_name: &str) -> String {
+    let mut retval = String::with_capacity(sm_name.len() + 4);
+    for (i, c) in sm_name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                retval.push('-');
+            }
+            retval.extend(c.to_lowercase());
+        } else {
+            retval.push(c);
+        }
+    }
+    retval
+}
+
+fn invalid(what: &str) -> IppError {
+    IppError::ValueError(format!("Invalid PWG SM XML: {}", what))
+}
+
+/// XML element as used by the Semantic Model: either text or child elements
+#[derive(Default)]
+struct Element {
+    name: String,
+    language: Option<String>,
+    text: String,
+    children: Vec<Element>
+}
+
+impl Element {
+    fn new(name: &str) -> Element {
+        Element { name: name.to_string(), ..Default::default() }
+    }
+
+    fn with_text(name: &str, text: String) -> Element {
+        Element { text, ..Element::new(name) }
+    }
+
+    fn child(&self, name: &str) -> Result<&Element> {
+        self.children.iter().find(|e| e.name == name).ok_or_else(|| invalid(&format!("missing {} in {}", name, self.name)))
+    }
+
+    fn child_names(&self) -> Vec<&str> {
+        self.children.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    // writing into a String cannot fail
+    fn write(&self, out: &mut String, indent: usize, namespace: Option<&str>) {
+        let _ = write!(out, "{:indent$}<{}", "", self.name, indent = indent);
+        if let Some(namespace) = namespace {
+            let _ = write!(out, " xmlns=\"{}\"", namespace);
+        }
+        if let Some(ref language) = self.language {
+            let _ = write!(out, " xml:lang=\"{}\"", escape(language));
+        }
+        if !self.children.is_empty() {
+            out.push_str(">\n");
+            for child in &self.children {
+                child.write(out, indent + 2, None);
+            }
+            let _ = writeln!(out, "{:indent$}</{}>", "", self.name, indent = indent);
+        } else if self.text.is_empty() {
+            out.push_str("/>\n");
+        } else {
+            let _ = writeln!(out, ">{}</{}>", escape(&self.text), self.name);
+        }
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut retval = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => retval.push_str("&amp;"),
+            '<' => retval.push_str("&lt;"),
+            '>' => retval.push_str("&gt;"),
+            '"' => retval.push_str("&quot;"),
+            _ => retval.push(c)
+        }
+    }
+    retval
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// element of the attribute value, None for out-of-band values
+fn value_element(attr: &str, value: &IppValue) -> Option<Element> {
+    let name = sm_name(attr);
+    match *value {
+        IppValue::ListOf(ref list) => list_element(attr, &name, list),
+        _ if is_set(attr) => list_element(attr, &name, slice::from_ref(value)),
+        _ => item_element(attr, &name, value)
+    }
+}
+
+// 1setOf element with one child per value, None if all values are out-of-band
+fn list_element(attr: &str, name: &str, list: &[IppValue]) -> Option<Element> {
+    let mut element = Element::new(name);
+    element.children.extend(list.iter().filter_map(|value| item_element(attr, item_name(name), value)));
+    if element.children.is_empty() { None } else { Some(element) }
+}
+
+fn item_element(attr: &str, name: &str, value: &IppValue) -> Option<Element> {
+    let mut element = Element::new(name);
+    match *value {
+        IppValue::Integer(i) => element.text = i.to_string(),
+        IppValue::Enum(i) => {
+            element.text = enum_names(attr).iter()
+                .find(|&&(known, _)| known == i)
+                .map_or_else(|| i.to_string(), |&(_, keyword)| sm_name(keyword));
+        }
+        IppValue::Boolean(b) => element.text = b.to_string(),
+        IppValue::Keyword(ref s) |
+        IppValue::TextWithoutLanguage(ref s) | IppValue::NameWithoutLanguage(ref s) |
+        IppValue::Charset(ref s) | IppValue::NaturalLanguage(ref s) |
+        IppValue::Uri(ref s) | IppValue::MimeMediaType(ref s) |
+        IppValue::MemberAttrName(ref s) | IppValue::UriScheme(ref s) => element.text = s.clone(),
+        IppValue::TextWithLanguage { ref language, text: ref s } |
+        IppValue::NameWithLanguage { ref language, name: ref s } => {
+            element.text = s.clone();
+            element.language = Some(language.clone());
+        }
+        IppValue::RangeOfInteger(lower, upper) => {
+            element.children.push(Element::with_text("Lower", lower.to_string()));
+            element.children.push(Element::with_text("Upper", upper.to_string()));
+        }
+        IppValue::Resolution(crossfeed, feed, units) => {
+            let units = match units {
+                3 => "dpi".to_string(),
+                4 => "dpcm".to_string(),
+                _ => units.to_string()
+            };
+            element.children.push(Element::with_text("CrossFeedDirResolution", crossfeed.to_string()));
+            element.children.push(Element::with_text("FeedDirResolution", feed.to_string()));
+            element.children.push(Element::with_text("Units", units));
+        }
+        IppValue::DateTime(ref dt) => element.text = dt.to_rfc3339(),
+        IppValue::ListOf(ref list) => return list_element(attr, name, list),
+        IppValue::Collection(ref members) => {
+            element.children.extend(members.iter().filter_map(|(member, value)| value_element(member, value)));
+        }
+        IppValue::OctetString(ref data) | IppValue::Other(_, ref data) => element.text = hex(data),
+        IppValue::Extension(tag, ref data) => element.text = hex(&tag.to_be_bytes()) + &hex(data),
+        IppValue::Unsupported | IppValue::Unknown | IppValue::NoValue |
+        IppValue::NotSettable | IppValue::DeleteAttribute | IppValue::AdminDefine => return None
+    }
+    Some(element)
+}
+
+fn group_element(name: &str, group: &IppAttributeGroup, filter: &dyn Fn(&IppAttribute) -> bool) -> Element {
+    let mut element = Element::new(name);
+    element.children.extend(group.iter()
+        .filter(|attr| filter(attr))
+        .filter_map(|attr| value_element(attr.name(), attr.value())));
+    element
+}
+
+fn document(root: &Element) -> String {
+    let mut retval = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string();
+    root.write(&mut retval, 0, Some(SM_NAMESPACE));
+    retval
+}
+
+fn parse_integer(element: &Element) -> Result<i32> {
+    element.text.trim().parse().map_err(|_| invalid(&format!("integer expected in {}", element.name)))
+}
+
+fn parse_enum(attr: &str, element: &Element) -> Result<i32> {
+    let text = element.text.trim();
+    match enum_names(attr).iter().find(|&&(_, keyword)| sm_name(keyword) == text) {
+        Some(&(value, _)) => Ok(value),
+        None => parse_integer(element)
+    }
+}
+
+fn parse_hex(element: &Element) -> Result<Vec<u8>> {
+    let text = element.text.trim();
+    let error = || invalid(&format!("hex string expected in {}", element.name));
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return Err(error());
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(&text[i..i + 2], 16).map_err(|_| error())).collect()
+}
+
+// value of the attribute with the given name, using its known syntax if there is one
+fn attribute_value(attr: &str, element: &Element) -> Result<IppValue> {
+    match known_syntax(attr) {
+        Some((tag, true)) => {
+            let item = item_name(&element.name);
+            if !element.children.is_empty() && element.children.iter().all(|e| e.name == item) {
+                Ok(IppValue::ListOf(element.children.iter()
+                    .map(|e| syntax_value(attr, tag, e))
+                    .collect::<Result<_>>()?))
+            } else {
+                // a single value which is not wrapped into an item element
+                Ok(IppValue::ListOf(vec![syntax_value(attr, tag, element)?]))
+            }
+        }
+        Some((tag, false)) => syntax_value(attr, tag, element),
+        None => infer_value(element)
+    }
+}
+
+fn syntax_value(attr: &str, tag: ValueTag, element: &Element) -> Result<IppValue> {
+    let text = || element.text.clone();
+    let retval = match tag {
+        ValueTag::Integer => IppValue::Integer(parse_integer(element)?),
+        ValueTag::Enum => IppValue::Enum(parse_enum(attr, element)?),
+        ValueTag::Boolean => match element.text.trim() {
+            "true" => IppValue::Boolean(true),
+            "false" => IppValue::Boolean(false),
+            _ => return Err(invalid(&format!("boolean expected in {}", element.name)))
+        },
+        ValueTag::Keyword => IppValue::Keyword(text()),
+        ValueTag::TextWithoutLanguage => match element.language {
+            Some(ref language) => IppValue::TextWithLanguage { language: language.clone(), text: text() },
+            None => IppValue::TextWithoutLanguage(text())
+        },
+        ValueTag::NameWithoutLanguage => match element.language {
+            Some(ref language) => IppValue::NameWithLanguage { language: language.clone(), name: text() },
+            None => IppValue::NameWithoutLanguage(text())
+        },
+        ValueTag::Charset => IppValue::Charset(text()),
+        ValueTag::NaturalLanguage => IppValue::NaturalLanguage(text()),
+        ValueTag::Uri => IppValue::Uri(text()),
+        ValueTag::MimeMediaType => IppValue::MimeMediaType(text()),
+        ValueTag::DateTime => IppValue::DateTime(IppDateTime::parse_rfc3339(element.text.trim())?),
+        ValueTag::RangeOfInteger => range_value(element)?,
+        ValueTag::Resolution => resolution_value(element)?,
+        ValueTag::BegCollection => collection_value(element)?,
+        ValueTag::OctectStringUnspecified => IppValue::OctetString(parse_hex(element)?),
+        _ => infer_value(element)?
+    };
+    Ok(retval)
+}
+
+// syntax of unknown attributes is inferred from the element
+fn infer_value(element: &Element) -> Result<IppValue> {
+    if !element.children.is_empty() {
+        let item = item_name(&element.name);
+        return match element.child_names().as_slice() {
+            names if names.iter().all(|name| *name == item) => {
+                Ok(IppValue::ListOf(element.children.iter().map(infer_value).collect::<Result<_>>()?))
+            }
+            ["Lower", "Upper"] => range_value(element),
+            ["CrossFeedDirResolution", "FeedDirResolution", "Units"] => resolution_value(element),
+            _ => collection_value(element)
+        };
+    }
+    if let Some(ref language) = element.language {
+        return Ok(IppValue::TextWithLanguage { language: language.clone(), text: element.text.clone() });
+    }
+    let text = element.text.as_str();
+    let retval = if text == "true" || text == "false" {
+        IppValue::Boolean(text == "true")
+    } else if let Ok(i) = text.parse() {
+        IppValue::Integer(i)
+    } else if let Ok(dt) = IppDateTime::parse_rfc3339(text) {
+        IppValue::DateTime(dt)
+    } else if text.contains("://") {
+        IppValue::Uri(text.to_string())
+    } else {
+        IppValue::Keyword(text.to_string())
+    };
+    Ok(retval)
+}
+
+fn range_value(element: &Element) -> Result<IppValue> {
+    Ok(IppValue::RangeOfInteger(parse_integer(element.child("Lower")?)?, parse_integer(element.child("Upper")?)?))
+}
+
+fn resolution_value(element: &Element) -> Result<IppValue> {
+    let units = element.child("Units")?;
+    let units = match units.text.trim() {
+        "dpi" => 3,
+        "dpcm" => 4,
+        text => text.parse::<i8>().map_err(|_| invalid(&format!("units expected in {}", units.name)))?
+    };
+    Ok(IppValue::Resolution(parse_integer(element.child("CrossFeedDirResolution")?)?,
+                            parse_integer(element.child("FeedDirResolution")?)?, units))
+}
+
+fn collection_value(element: &Element) -> Result<IppValue> {
+    Ok(IppValue::Collection(attributes(&element.children)?
+        .into_iter()
+        .map(|attr| (attr.name().to_string(), attr.value().clone()))
+        .collect()))
+}
+
+fn attributes(elements: &[Element]) -> Result<Vec<IppAttribute>> {
+    elements.iter()
+        .map(|element| {
+            let name = ipp_name(&element.name);
+            let value = attribute_value(&name, element)?;
+            Ok(IppAttribute::new(&name, value))
+        })
+        .collect()
+}
+
+// the root, group, attribute and value elements come on top of the collection nesting
+fn max_depth() -> usize {
+    ParseLimits::default().max_depth + 4
+}
+
+// elements are nested at most max_depth() deep, which bounds the recursion of infer_value
+fn read_element(xml: &str) -> Result<Element> {
+    let mut stack: Vec<Element> = Vec::new();
+    for event in EventReader::from_str(xml) {
+        match event.map_err(|e| invalid(&e.to_string()))? {
+            XmlEvent::StartElement { name, attributes, .. } => {
+                if stack.len() >= max_depth() {
+                    return Err(invalid(&format!("elements nested deeper than {}", max_depth())));
+                }
+                let mut element = Element::new(&name.local_name);
+                for attr in attributes {
+                    if attr.name.prefix.as_deref() == Some("xml") && attr.name.local_name == "lang" {
+                        element.language = Some(attr.value);
+                    }
+                }
+                stack.push(element);
+            }
+            XmlEvent::EndElement { .. } => {
+                let element = stack.pop().ok_or_else(|| invalid("unbalanced elements"))?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => return Ok(element)
+                }
+            }
+            XmlEvent::Characters(s) | XmlEvent::CData(s) | XmlEvent::Whitespace(s) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&s);
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(invalid("no root element"))
+}
+
+fn add_group(list: &mut IppAttributeList, tag: DelimiterTag, element: &Element) -> Result<()> {
+    for attr in attributes(&element.children)? {
+        list.add(tag, attr);
+    }
+    Ok(())
+}
+
+fn job_ticket(list: &IppAttributeList) -> Element {
+    let mut root = Element::new("JobTicket");
+    if let Some(group) = list.get_group(DelimiterTag::DocumentAttributes) {
+        root.children.push(group_element("DocumentProcessing", group, &|_| true));
+    }
+    if let Some(group) = list.get_group(DelimiterTag::JobAttributes) {
+        root.children.push(group_element("JobDescription", group, &is_job_description));
+        root.children.push(group_element("JobProcessing", group, &is_job_processing));
+    }
+    root.children.retain(|e| !e.children.is_empty());
+    root
+}
+
+fn add_job_ticket(list: &mut IppAttributeList, ticket: &Element) -> Result<()> {
+    for element in &ticket.children {
+        match element.name.as_str() {
+            "JobDescription" | "JobProcessing" => add_group(list, DelimiterTag::JobAttributes, element)?,
+            "DocumentProcessing" => add_group(list, DelimiterTag::DocumentAttributes, element)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+impl IppAttributeList {
+    /// Export job and document attributes as PWG SM `JobTicket` document,
+    /// job status attributes are left out
+    pub fn to_sm_job_ticket(&self) -> String {
+        document(&job_ticket(self))
+    }
+
+    /// Export job and document attributes as PWG SM `Job` document with `JobStatus` and `JobTicket`
+    pub fn to_sm_job(&self) -> String {
+        let mut root = Element::new("Job");
+        if let Some(group) = self.get_group(DelimiterTag::JobAttributes) {
+            root.children.push(group_element("JobStatus", group, &is_job_status));
+        }
+        root.children.push(job_ticket(self));
+        root.children.retain(|e| !e.children.is_empty());
+        document(&root)
+    }
+
+    /// Export printer attributes as PWG SM `PrinterDescription` document,
+    /// printer status attributes are left out
+    pub fn to_sm_printer_description(&self) -> String {
+        let root = match self.get_group(DelimiterTag::PrinterAttributes) {
+            Some(group) => group_element("PrinterDescription", group, &|attr| !is_printer_status(attr)),
+            None => Element::new("PrinterDescription")
+        };
+        document(&root)
+    }
+
+    /// Export printer attributes as PWG SM `Printer` document with `PrinterDescription` and `PrinterStatus`
+    pub fn to_sm_printer(&self) -> String {
+        let mut root = Element::new("Printer");
+        if let Some(group) = self.get_group(DelimiterTag::PrinterAttributes) {
+            root.children.push(group_element("PrinterDescription", group, &|attr| !is_printer_status(attr)));
+            root.children.push(group_element("PrinterStatus", group, &is_printer_status));
+            root.children.retain(|e| !e.children.is_empty());
+        }
+        document(&root)
+    }
+
+    /// Import PWG SM `Job`, `JobTicket`, `DocumentProcessing`, `Printer` or `PrinterDescription` document
+    pub fn from_sm_xml(xml: &str) -> Result<IppAttributeList> {
+        let root = read_element(xml)?;
+        let mut retval = IppAttributeList::new();
+        match root.name.as_str() {
+            "Job" => {
+                for element in &root.children {
+                    match element.name.as_str() {
+                        "JobStatus" => add_group(&mut retval, DelimiterTag::JobAttributes, element)?,
+                        "JobTicket" => add_job_ticket(&mut retval, element)?,
+                        _ => {}
+                    }
+                }
+            }
+            "JobTicket" => add_job_ticket(&mut retval, &root)?,
+            "DocumentProcessing" => add_group(&mut retval, DelimiterTag::DocumentAttributes, &root)?,
+            "Printer" => {
+                for element in &root.children {
+                    match element.name.as_str() {
+                        "PrinterDescription" | "PrinterStatus" =>
+                            add_group(&mut retval, DelimiterTag::PrinterAttributes, element)?,
+                        _ => {}
+                    }
+                }
+            }
+            "PrinterDescription" => add_group(&mut retval, DelimiterTag::PrinterAttributes, &root)?,
+            name => return Err(invalid(&format!("unsupported element {}", name)))
+        }
+        Ok(retval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::*;
+    use parser::IppParser;
+    use IppHeader;
+
+    fn job_attributes() -> IppAttributeList {
+        let mut list = IppAttributeList::new();
+        let job = |name: &str, value: IppValue| IppAttribute::new(name, value);
+        list.add(DelimiterTag::JobAttributes, job("job-id", IppValue::Integer(5)));
+        list.add(DelimiterTag::JobAttributes, job("job-state", IppValue::Enum(5)));
+        list.add(DelimiterTag::JobAttributes, job("job-state-reasons", IppValue::Keyword("job-printing".into())));
+        list.add(DelimiterTag::JobAttributes, job("job-name", IppValue::NameWithoutLanguage("report".into())));
+        list.add(DelimiterTag::JobAttributes, job("job-originating-user-uri", IppValue::Uri("mailto:user@example.com".into())));
+        list.add(DelimiterTag::JobAttributes, job("job-message-to-operator",
+            IppValue::TextWithLanguage { language: "de".into(), text: "Bitte heften".into() }));
+        list.add(DelimiterTag::JobAttributes, job("copies", IppValue::Integer(2)));
+        list.add(DelimiterTag::JobAttributes, job("finishings", IppValue::ListOf(vec![IppValue::Enum(4), IppValue::Enum(5)])));
+        list.add(DelimiterTag::JobAttributes, job("page-ranges", IppValue::RangeOfInteger(1, 3)));
+        list.add(DelimiterTag::JobAttributes, job("media-col", IppValue::Collection(vec![
+            ("media-size".into(), IppValue::Collection(vec![
+                ("x-dimension".into(), IppValue::Integer(21000)),
+                ("y-dimension".into(), IppValue::Integer(29700))])),
+            ("media-type".into(), IppValue::Keyword("stationery".into())),
+            ("media-source".into(), IppValue::NoValue)])));
+        list
+    }
+
+    fn encoded(attr: &IppAttribute) -> Vec<u8> {
+        let mut data = Vec::new();
+        attr.write(&mut data).unwrap();
+        data
+    }
+
+    // elements follow the Semantic Model schema rules: no attributes but the namespace and xml:lang,
+    // known 1setOf attributes have at least one item child and enums are keyword names
+    fn check_schema(xml: &str) {
+        assert!(xml.contains(&format!(" xmlns=\"{}\"", SM_NAMESPACE)));
+        assert_eq!(xml.matches('=').count(), xml.matches("xmlns=").count() + xml.matches("xml:lang=").count() + 2);
+
+        fn check(element: &Element) {
+            let attr = ipp_name(&element.name);
+            let items: Vec<&Element> = if is_set(&attr) {
+                assert!(!element.children.is_empty(), "{} has no items", element.name);
+                for item in &element.children {
+                    assert_eq!(item.name, item_name(&element.name), "item of {}", element.name);
+                }
+                element.children.iter().collect()
+            } else {
+                vec![element]
+            };
+            for item in items {
+                if known_syntax(&attr).is_some_and(|(tag, _)| tag == ValueTag::Enum) {
+                    assert!(item.text.parse::<i32>().is_err(), "{} is not a keyword", item.name);
+                }
+                for child in &item.children {
+                    check(child);
+                }
+            }
+        }
+        check(&read_element(xml).unwrap());
+    }
+
+    #[test]
+    fn test_job_ticket() {
+        let xml = job_attributes().to_sm_job_ticket();
+        check_schema(&xml);
+        assert!(!xml.contains("JobId"));
+        assert!(!xml.contains("MediaSource"));
+        assert!(xml.contains("<JobDescription>\n    <JobName>report</JobName>"));
+        assert!(xml.contains("<Finishings>\n      <Finishing>Staple</Finishing>\n      <Finishing>Punch</Finishing>\n    </Finishings>"));
+
+        let list = IppAttributeList::from_sm_xml(&xml).unwrap();
+        match *list.get(DelimiterTag::JobAttributes, "finishings").unwrap().value() {
+            IppValue::ListOf(ref list) => assert_eq!(list.len(), 2),
+            ref value => panic!("unexpected value {:?}", value)
+        }
+        assert!(list.get(DelimiterTag::JobAttributes, "media-col").is_some());
+        assert!(list.get(DelimiterTag::JobAttributes, "job-id").is_none());
+    }
+
+    #[test]
+    fn test_job_status() {
+        let xml = job_attributes().to_sm_job();
+        check_schema(&xml);
+        let status = xml.find("<JobStatus>").unwrap();
+        let ticket = xml.find("<JobTicket>").unwrap();
+        assert!(status < xml.find("<JobId>").unwrap() && xml.find("<JobState>").unwrap() < ticket);
+        assert!(xml.find("<Copies>").unwrap() > xml.find("<JobProcessing>").unwrap());
+        assert!(xml.contains("<JobState>Processing</JobState>"));
+    }
+
+    #[test]
+    fn test_single_valued_sets() {
+        let xml = job_attributes().to_sm_job();
+        check_schema(&xml);
+        assert!(xml.contains("<JobStateReasons>\n      <JobStateReason>job-printing</JobStateReason>\n    </JobStateReasons>"));
+        assert!(xml.contains("<PageRanges>\n        <PageRange>\n          <Lower>1</Lower>"));
+
+        // the parser gives a scalar for a 1setOf with one value
+        let mut data = Vec::new();
+        IppHeader::new(0x0101, 0, 1).write(&mut data).unwrap();
+        let mut printer = IppAttributeGroup::new(DelimiterTag::PrinterAttributes);
+        printer.add(IppAttribute::new("media-supported", IppValue::Keyword("iso_a4_210x297mm".into())));
+        printer.add(IppAttribute::new("printer-state-reasons", IppValue::Keyword("none".into())));
+        let mut list = IppAttributeList::new();
+        list.add_group(printer);
+        list.write(&mut data).unwrap();
+        let list = IppParser::new(&mut Cursor::new(data)).parse().unwrap().into_parts().1;
+
+        let xml = list.to_sm_printer();
+        check_schema(&xml);
+        assert!(xml.contains("<MediaSupported>\n      <Media>iso_a4_210x297mm</Media>\n    </MediaSupported>"));
+        assert!(xml.contains("<PrinterStateReasons>\n      <PrinterStateReason>none</PrinterStateReason>"));
+    }
+
+    #[test]
+    fn test_lossless_round_trip() {
+        let list = job_attributes();
+        let parsed = IppAttributeList::from_sm_xml(&list.to_sm_job()).unwrap();
+        for attr in list.get_group(DelimiterTag::JobAttributes).unwrap() {
+            let imported = parsed.get(DelimiterTag::JobAttributes, attr.name()).unwrap();
+            if attr.name() == "media-col" {
+                // the out-of-band member is left out
+                assert_eq!(format!("{:?}", imported.value()),
+                           format!("{:?}", IppValue::Collection(vec![
+                               ("media-size".into(), IppValue::Collection(vec![
+                                   ("x-dimension".into(), IppValue::Integer(21000)),
+                                   ("y-dimension".into(), IppValue::Integer(29700))])),
+                               ("media-type".into(), IppValue::Keyword("stationery".into()))])));
+            } else {
+                assert_eq!(encoded(imported), encoded(attr), "{}", attr.name());
+            }
+        }
+
+        // unknown enum values and attributes are kept as numbers and inferred
+        let xml = "<JobTicket><JobProcessing><Finishings><Finishing>99</Finishing></Finishings>\
+                   <VendorCount>3</VendorCount></JobProcessing></JobTicket>";
+        let parsed = IppAttributeList::from_sm_xml(xml).unwrap();
+        let finishings = parsed.get(DelimiterTag::JobAttributes, "finishings").unwrap();
+        assert_eq!(encoded(finishings), encoded(&IppAttribute::new("finishings", IppValue::Enum(99))));
+        let vendor = parsed.get(DelimiterTag::JobAttributes, "vendor-count").unwrap();
+        assert_eq!(encoded(vendor), encoded(&IppAttribute::new("vendor-count", IppValue::Integer(3))));
+        assert!(IppAttributeList::from_sm_xml("<JobTicket><JobProcessing><Copies>two</Copies></JobProcessing></JobTicket>").is_err());
+    }
+
+    #[test]
+    fn test_printer() {
+        let mut list = IppAttributeList::new();
+        list.add(DelimiterTag::PrinterAttributes, IppAttribute::new("printer-state", IppValue::Enum(3)));
+        list.add(DelimiterTag::PrinterAttributes, IppAttribute::new("printer-alert", IppValue::OctetString(vec![0x01, 0xfe])));
+        list.add(DelimiterTag::PrinterAttributes, IppAttribute::new("media-supported",
+            IppValue::ListOf(vec![IppValue::Keyword("iso_a4_210x297mm".into())])));
+
+        let xml = list.to_sm_printer_description();
+        check_schema(&xml);
+        assert!(!xml.contains("PrinterState"));
+        assert!(xml.contains("<MediaSupported>\n    <Media>iso_a4_210x297mm</Media>\n  </MediaSupported>"));
+
+        let xml = list.to_sm_printer();
+        check_schema(&xml);
+        assert!(xml.contains("<PrinterStatus>\n    <PrinterState>Idle</PrinterState>"));
+        let parsed = IppAttributeList::from_sm_xml(&xml).unwrap();
+        for attr in list.get_group(DelimiterTag::PrinterAttributes).unwrap() {
+            let imported = parsed.get(DelimiterTag::PrinterAttributes, attr.name()).unwrap();
+            assert_eq!(encoded(imported), encoded(attr), "{}", attr.name());
+        }
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let depth = 10_000;
+        let xml = format!("<JobTicket><JobProcessing>{}{}</JobProcessing></JobTicket>",
+                          "<MediaCol>".repeat(depth), "</MediaCol>".repeat(depth));
+        assert!(IppAttributeList::from_sm_xml(&xml).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//!
+//! Operation targets and mapping of IPP URIs to HTTP URLs
+//!
+use hyper::Url;
+
+use ::{Result, IppError};
+use attribute::IppAttribute;
+use value::IppValue;
+use consts::attribute::{PRINTER_URI, JOB_URI, JOB_ID, SYSTEM_URI, DOCUMENT_NUMBER};
+
+/// Default port of the ipp and ipps schemes, RFC 3510 and RFC 7472
+pub const IPP_DEFAULT_PORT: u16 = 631;
+
+/// Convert http or https URI to the ipp or ipps URI of the same endpoint.
+/// Other URIs are returned as is.
+pub fn ipp_uri(uri: &str) -> String {
+    let mut url = match Url::parse(uri) {
+        Ok(url) => url,
+        Err(_) => return uri.to_string()
+    };
+    let scheme = match url.scheme() {
+        "http" => "ipp",
+        "https" => "ipps",
+        _ => return uri.to_string()
+    };
+    // the http default port is implicit and must be kept when the scheme changes
+    let port = url.port_or_known_default();
+    if url.set_scheme(scheme).is_err() || url.set_port(port).is_err() {
+        return uri.to_string();
+    }
+    url.into_string()
+}
+
+/// Convert ipp or ipps URI to the http or https URL of the same endpoint,
+/// http and https URLs are returned as is
+pub fn http_url(uri: &str) -> Result<Url> {
+    let mut url = Url::parse(uri).map_err(|e| IppError::RequestError(format!("Invalid URI {}: {}", uri, e)))?;
+    let scheme = match url.scheme() {
+        "http" | "https" => return Ok(url),
+        "ipp" => "http",
+        "ipps" => "https",
+        scheme => return Err(IppError::RequestError(format!("Unsupported URI scheme {}", scheme)))
+    };
+    let port = url.port().unwrap_or(IPP_DEFAULT_PORT);
+    url.set_scheme(scheme).map_err(|_| IppError::RequestError(format!("Invalid URI {}", uri)))?;
+    url.set_port(Some(port)).map_err(|_| IppError::RequestError(format!("Invalid URI {}", uri)))?;
+    Ok(url)
+}
+
+/// Object an operation is applied to, RFC 8011 section 4.1.5.
+/// http and https URIs are sent as ipp and ipps URIs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IppTarget {
+    /// Printer given by `printer-uri`
+    Printer(String),
+    /// Job given by `job-uri`
+    Job(String),
+    /// Job given by `printer-uri` and `job-id`
+    PrinterJob(String, i32),
+    /// System given by `system-uri`, PWG 5100.22
+    System(String),
+    /// Document given by the job target and `document-number`, PWG 5100.5
+    Document(Box<IppTarget>, i32),
+}
+
+impl IppTarget {
+    /// Create target of the document with the given number of the job target
+    pub fn document(job: IppTarget, number: i32) -> IppTarget {
+        IppTarget::Document(Box::new(job), number)
+    }
+
+    /// Return URI of the printer, job or system as given
+    pub fn uri(&self) -> &str {
+        match *self {
+            IppTarget::Printer(ref uri) | IppTarget::Job(ref uri) |
+            IppTarget::PrinterJob(ref uri, _) | IppTarget::System(ref uri) => uri,
+            IppTarget::Document(ref job, _) => job.uri(),
+        }
+    }
+
+    /// Return operation attributes which address the target
+    pub fn attributes(&self) -> Vec<IppAttribute> {
+        let uri = |name: &str, uri: &str| IppAttribute::new(name, IppValue::Uri(ipp_uri(uri)));
+        match *self {
+            IppTarget::Printer(ref printer) => vec![uri(PRINTER_URI, printer)],
+            IppTarget::Job(ref job) => vec![uri(JOB_URI, job)],
+            IppTarget::PrinterJob(ref printer, job_id) =>
+                vec![uri(PRINTER_URI, printer), IppAttribute::new(JOB_ID, IppValue::Integer(job_id))],
+            IppTarget::System(ref system) => vec![uri(SYSTEM_URI, system)],
+            IppTarget::Document(ref job, number) => {
+                let mut retval = job.attributes();
+                retval.push(IppAttribute::new(DOCUMENT_NUMBER, IppValue::Integer(number)));
+                retval
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ipp_uri() {
+        assert_eq!(ipp_uri("http://localhost:631/printers/test"), "ipp://localhost:631/printers/test");
+        assert_eq!(ipp_uri("https://localhost:8443/printers/test"), "ipps://localhost:8443/printers/test");
+        // the default http port is not the default ipp port
+        assert_eq!(ipp_uri("http://localhost/printers/test"), "ipp://localhost:80/printers/test");
+        assert_eq!(ipp_uri("https://localhost/printers/test"), "ipps://localhost:443/printers/test");
+        assert_eq!(ipp_uri("ipp://localhost/printers/test"), "ipp://localhost/printers/test");
+        assert_eq!(ipp_uri("ipps://localhost:8631/printers/test"), "ipps://localhost:8631/printers/test");
+        assert_eq!(ipp_uri("not a uri"), "not a uri");
+    }
+
+    #[test]
+    fn test_http_url() {
+        let url = |uri: &str| http_url(uri).unwrap().into_string();
+        assert_eq!(url("ipp://localhost/printers/test"), "http://localhost:631/printers/test");
+        assert_eq!(url("ipps://localhost/printers/test"), "https://localhost:631/printers/test");
+        assert_eq!(url("ipp://localhost:8631/printers/test"), "http://localhost:8631/printers/test");
+        assert_eq!(url("ipps://localhost:443/printers/test"), "https://localhost/printers/test");
+        assert_eq!(url("http://localhost/printers/test"), "http://localhost/printers/test");
+        assert_eq!(url("https://localhost:8443/printers/test"), "https://localhost:8443/printers/test");
+        assert!(http_url("ftp://localhost/printers/test").is_err());
+        assert!(http_url("not a uri").is_err());
+    }
+
+    #[test]
+    fn test_attributes() {
+        let names = |target: &IppTarget| target.attributes().iter().map(|attr| attr.name().to_string()).collect::<Vec<_>>();
+        assert_eq!(names(&IppTarget::Printer("ipp://localhost/printers/test".to_string())), [PRINTER_URI]);
+        assert_eq!(names(&IppTarget::Job("ipp://localhost/jobs/7".to_string())), [JOB_URI]);
+        assert_eq!(names(&IppTarget::System("ipp://localhost/ipp/system".to_string())), [SYSTEM_URI]);
+
+        let job = IppTarget::PrinterJob("https://localhost/printers/test".to_string(), 7);
+        let document = IppTarget::document(job.clone(), 2);
+        assert_eq!(document.uri(), "https://localhost/printers/test");
+        let attributes = document.attributes();
+        assert_eq!(names(&document), [PRINTER_URI, JOB_ID, DOCUMENT_NUMBER]);
+        match (attributes[0].value(), attributes[1].value(), attributes[2].value()) {
+            (IppValue::Uri(uri), IppValue::Integer(7), IppValue::Integer(2)) =>
+                assert_eq!(uri, "ipps://localhost:443/printers/test"),
+            values => panic!("unexpected values {:?}", values)
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for uri in &["ipp://localhost:631/printers/test", "ipps://localhost:631/printers/test",
+                     "ipp://localhost:8631/printers/test"] {
+            assert_eq!(ipp_uri(http_url(uri).unwrap().as_str()), *uri);
+        }
+    }
+}
@@ -0,0 +1,576 @@
+//!
+//! Text notation of IPP values, compatible with the one used by ipptool
+//!
+//! `Display` writes values in ipptool notation: `5`, `1-10`, `600dpi`, `one,two` or
+//! `{media-size={x-dimension=21000 y-dimension=29700}}`. Strings which would be ambiguous
+//! are double-quoted with backslash escapes. The syntax is not written, so it is lost for
+//! collection members and for the items of a 1setOf with several syntaxes.
+//!
+//! `IppValue::to_text` writes the syntax in front of the value and extends the notation
+//! where ipptool has no way to keep the syntax, so that `str::parse` gives back the same
+//! value. Collection members whose syntax cannot be inferred from the text are written as
+//! `name:syntax=value`. The items of a 1setOf with several syntaxes are written as
+//! `syntax:value`, or just the syntax for out-of-band values, and the set has the syntax
+//! `mixed`, for example `1setOf mixed keyword:a4,name:custom,no-value`. Values with an unknown
+//! tag have the tag in hex as syntax, for example `0x7f`, and resolution units other than
+//! dpi and dpcm are written as `u` followed by the number.
+//!
+use std::fmt::{self, Write};
+use std::str::{self, FromStr};
+
+use ::{Result, IppError};
+use consts::tag::ValueTag;
+use datetime::IppDateTime;
+use value::IppValue;
+
+fn invalid(syntax: &str, text: &str) -> IppError {
+    IppError::ValueError(format!("Invalid {} value '{}'", syntax, text))
+}
+
+fn needs_quotes(s: &str, nested: bool) -> bool {
+    s.is_empty()
+        || s.starts_with(char::is_whitespace) || s.ends_with(char::is_whitespace)
+        || s.starts_with('{') || s.starts_with('<')
+        || s.contains([',', '"', '\\'])
+        || (nested && s.contains(|c: char| c.is_whitespace() || c == '{' || c == '}'))
+}
+
+fn write_quoted(f: &mut dyn Write, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        if c == '"' || c == '\\' {
+            f.write_char('\\')?;
+        }
+        f.write_char(c)?;
+    }
+    f.write_char('"')
+}
+
+fn write_string(f: &mut dyn Write, s: &str, nested: bool) -> fmt::Result {
+    if needs_quotes(s, nested) {
+        write_quoted(f, s)
+    } else {
+        f.write_str(s)
+    }
+}
+
+fn write_hex(f: &mut dyn Write, data: &[u8]) -> fmt::Result {
+    f.write_char('<')?;
+    for b in data {
+        write!(f, "{:02x}", b)?;
+    }
+    f.write_char('>')
+}
+
+// syntax of a 1setOf whose items have different syntaxes
+const MIXED: &str = "mixed";
+
+// syntax name usable with IppValue::parse, raw tags are written in hex
+fn item_syntax(value: &IppValue) -> String {
+    match *value {
+        IppValue::ListOf(_) if is_mixed(value) => MIXED.to_string(),
+        IppValue::ListOf(ref list) => list.first().map_or_else(|| "no-value".to_string(), item_syntax),
+        IppValue::Other(tag, _) => format!("0x{:02x}", tag),
+        _ => value.syntax().to_string()
+    }
+}
+
+fn is_mixed(value: &IppValue) -> bool {
+    match *value {
+        IppValue::ListOf(ref list) => list.windows(2).any(|pair| item_syntax(&pair[0]) != item_syntax(&pair[1])),
+        _ => false
+    }
+}
+
+fn to_text(value: &IppValue, nested: bool, extended: bool) -> String {
+    let mut s = String::new();
+    // writing into a String cannot fail
+    let _ = write_value(&mut s, value, nested, extended);
+    s
+}
+
+fn tags(value: &IppValue) -> Vec<u8> {
+    value.into_iter().map(IppValue::to_tag).collect()
+}
+
+// ipptool notation, `extended` adds what is needed to read the value back without loss
+fn write_value(f: &mut dyn Write, value: &IppValue, nested: bool, extended: bool) -> fmt::Result {
+    match *value {
+        IppValue::Integer(i) | IppValue::Enum(i) => write!(f, "{}", i),
+        IppValue::RangeOfInteger(min, max) => write!(f, "{}-{}", min, max),
+        IppValue::Boolean(b) => f.write_str(if b { "true" } else { "false" }),
+        IppValue::Keyword(ref s) |
+        IppValue::TextWithoutLanguage(ref s) | IppValue::NameWithoutLanguage(ref s) |
+        IppValue::Charset(ref s) | IppValue::NaturalLanguage(ref s) |
+        IppValue::Uri(ref s) | IppValue::MimeMediaType(ref s) |
+        IppValue::MemberAttrName(ref s) | IppValue::UriScheme(ref s) => write_string(f, s, nested),
+        IppValue::OctetString(ref vec) => {
+            // binary data and text which looks like hex is written as hex
+            match str::from_utf8(vec) {
+                Ok(s) if !s.starts_with('<') && !s.chars().any(char::is_control) => write_string(f, s, nested),
+                _ => write_hex(f, vec)
+            }
+        }
+        IppValue::TextWithLanguage { ref language, text: ref s } |
+        IppValue::NameWithLanguage { ref language, name: ref s } => {
+            if needs_quotes(s, nested) || s.contains('[') {
+                write_quoted(f, s)?;
+            } else {
+                f.write_str(s)?;
+            }
+            // no whitespace inside collections, it would end the member value
+            write!(f, "{}[{}]", if nested { "" } else { " " }, language)
+        }
+        IppValue::DateTime(ref dt) => write!(f, "{}", dt),
+        IppValue::Resolution(crossfeed, feed, units) => {
+            if crossfeed == feed {
+                write!(f, "{}", crossfeed)?;
+            } else {
+                write!(f, "{}x{}", crossfeed, feed)?;
+            }
+            // ipptool writes any units but dpi as dpcm
+            match units {
+                3 => f.write_str("dpi"),
+                _ if units == 4 || !extended => f.write_str("dpcm"),
+                _ => write!(f, "u{}", units)
+            }
+        }
+        IppValue::ListOf(ref list) => {
+            let mixed = extended && is_mixed(value);
+            for (i, item) in list.iter().enumerate() {
+                if i > 0 {
+                    f.write_char(',')?;
+                }
+                // out-of-band values are written as their syntax
+                if mixed && !item.is_out_of_band() {
+                    write!(f, "{}:", item_syntax(item))?;
+                }
+                write_value(f, item, nested, extended)?;
+            }
+            Ok(())
+        }
+        IppValue::Collection(ref members) => {
+            f.write_char('{')?;
+            for (i, (name, member)) in members.iter().enumerate() {
+                if i > 0 {
+                    f.write_char(' ')?;
+                }
+                let text = to_text(member, true, extended);
+                // annotate the syntax when reading the text back would infer another one
+                match parse_member(None, &text) {
+                    Ok(ref inferred) if !extended || tags(inferred) == tags(member) => write!(f, "{}={}", name, text)?,
+                    _ => write!(f, "{}:{}={}", name, item_syntax(member), text)?
+                }
+            }
+            f.write_char('}')
+        }
+        IppValue::Unsupported | IppValue::Unknown | IppValue::NoValue |
+        IppValue::NotSettable | IppValue::DeleteAttribute | IppValue::AdminDefine => f.write_str(value.syntax()),
+        IppValue::Extension(tag, ref vec) => {
+            let mut data = Vec::with_capacity(4 + vec.len());
+            data.extend_from_slice(&tag.to_be_bytes());
+            data.extend_from_slice(vec);
+            write_hex(f, &data)
+        }
+        IppValue::Other(_, ref vec) => write_hex(f, vec),
+    }
+}
+
+/// Write the value in ipptool notation, without its syntax
+impl fmt::Display for IppValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_value(f, self, false, false)
+    }
+}
+
+// scan text up to the first character matching `stop` outside of quotes and braces,
+// returns the byte offset
+fn scan(s: &str, stop: &dyn Fn(char) -> bool) -> usize {
+    let mut depth = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if quoted {
+            match c {
+                '\\' => escaped = true,
+                '"' => quoted = false,
+                _ => {}
+            }
+        } else if depth == 0 && stop(c) {
+            return i;
+        } else {
+            match c {
+                '"' => quoted = true,
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+    s.len()
+}
+
+// split 1setOf values on top-level commas
+fn split_items(s: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut rest = s;
+    loop {
+        let end = scan(rest, &|c| c == ',');
+        items.push(rest[..end].trim());
+        if end == rest.len() {
+            return items;
+        }
+        rest = &rest[end + 1..];
+    }
+}
+
+// read a quoted string, returns the unescaped string and the remaining text
+fn unquote(s: &str) -> Option<(String, &str)> {
+    if !s.starts_with('"') {
+        return None;
+    }
+    let mut retval = String::new();
+    let mut escaped = false;
+    for (i, c) in s.char_indices().skip(1) {
+        if escaped {
+            retval.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            return Some((retval, &s[i + 1..]));
+        } else {
+            retval.push(c);
+        }
+    }
+    None
+}
+
+fn parse_string(syntax: &str, s: &str) -> Result<String> {
+    if s.starts_with('"') {
+        match unquote(s) {
+            Some((retval, "")) => Ok(retval),
+            _ => Err(invalid(syntax, s))
+        }
+    } else {
+        Ok(s.to_string())
+    }
+}
+
+fn parse_with_language(syntax: &str, s: &str) -> Result<(String, String)> {
+    let (text, rest) = match unquote(s) {
+        Some((text, rest)) => (text, rest.trim_start()),
+        None => match s.rfind('[') {
+            Some(pos) => (s[..pos].trim_end().to_string(), &s[pos..]),
+            None => return Err(invalid(syntax, s))
+        }
+    };
+    if rest.len() > 2 && rest.starts_with('[') && rest.ends_with(']') {
+        Ok((rest[1..rest.len() - 1].to_string(), text))
+    } else {
+        Err(invalid(syntax, s))
+    }
+}
+
+fn parse_hex(syntax: &str, s: &str) -> Result<Vec<u8>> {
+    if s.len() < 2 || !s.starts_with('<') || !s.ends_with('>') || !s.len().is_multiple_of(2) {
+        return Err(invalid(syntax, s));
+    }
+    let hex = &s[1..s.len() - 1];
+    (0..hex.len()).step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| invalid(syntax, s))
+}
+
+fn parse_integer(syntax: &str, s: &str) -> Result<i32> {
+    s.parse().map_err(|_| invalid(syntax, s))
+}
+
+fn parse_range(s: &str) -> Result<(i32, i32)> {
+    // skip the sign of the lower bound when looking for the separator
+    match s.get(1..).and_then(|rest| rest.find('-')) {
+        Some(pos) => Ok((parse_integer("rangeOfInteger", &s[..pos + 1])?,
+                         parse_integer("rangeOfInteger", &s[pos + 2..])?)),
+        None => parse_integer("rangeOfInteger", s).map(|i| (i, i))
+    }
+}
+
+fn parse_resolution(s: &str) -> Result<(i32, i32, i8)> {
+    let (value, units) = if let Some(value) = s.strip_suffix("dpi") {
+        (value, 3)
+    } else if let Some(value) = s.strip_suffix("dpcm") {
+        (value, 4)
+    } else {
+        match s.rfind('u') {
+            Some(pos) => (&s[..pos], s[pos + 1..].parse::<i8>().map_err(|_| invalid("resolution", s))?),
+            None => return Err(invalid("resolution", s))
+        }
+    };
+    let (crossfeed, feed) = match value.find('x') {
+        Some(pos) => (&value[..pos], &value[pos + 1..]),
+        None => (value, value)
+    };
+    Ok((parse_integer("resolution", crossfeed)?, parse_integer("resolution", feed)?, units))
+}
+
+fn parse_collection(s: &str) -> Result<IppValue> {
+    if s.len() < 2 || !s.starts_with('{') || !s.ends_with('}') {
+        return Err(invalid("collection", s));
+    }
+    let mut members = Vec::new();
+    let mut rest = s[1..s.len() - 1].trim_start();
+    while !rest.is_empty() {
+        let eq = rest.find('=').ok_or_else(|| invalid("collection", s))?;
+        let (name, syntax) = match rest[..eq].find(':') {
+            Some(pos) => (&rest[..pos], Some(&rest[pos + 1..eq])),
+            None => (&rest[..eq], None)
+        };
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(invalid("collection", s));
+        }
+        rest = &rest[eq + 1..];
+        let end = scan(rest, &char::is_whitespace);
+        members.push((name.to_string(), parse_member(syntax, &rest[..end])?));
+        rest = rest[end..].trim_start();
+    }
+    Ok(IppValue::Collection(members))
+}
+
+// read collection member value, syntax is inferred from the text unless given
+fn parse_member(syntax: Option<&str>, s: &str) -> Result<IppValue> {
+    let mut values = split_items(s).into_iter()
+        .map(|item| match syntax {
+            Some(MIXED) => parse_mixed_item(item),
+            Some(syntax) => parse_item(syntax, item),
+            None => infer_item(item)
+        })
+        .collect::<Result<Vec<IppValue>>>()?;
+    if values.len() == 1 {
+        Ok(values.remove(0))
+    } else {
+        Ok(IppValue::ListOf(values))
+    }
+}
+
+fn infer_item(s: &str) -> Result<IppValue> {
+    if s.starts_with('{') {
+        return parse_collection(s);
+    }
+    if s.starts_with('"') {
+        return parse_string("textWithoutLanguage", s).map(IppValue::TextWithoutLanguage);
+    }
+    if s.starts_with('<') {
+        return parse_hex("octetString", s).map(IppValue::OctetString);
+    }
+    if s == "true" || s == "false" {
+        return Ok(IppValue::Boolean(s == "true"));
+    }
+    if let Ok(i) = s.parse() {
+        return Ok(IppValue::Integer(i));
+    }
+    if let Ok((min, max)) = parse_range(s) {
+        return Ok(IppValue::RangeOfInteger(min, max));
+    }
+    if let Ok((crossfeed, feed, units)) = parse_resolution(s) {
+        return Ok(IppValue::Resolution(crossfeed, feed, units));
+    }
+    if let Ok(dt) = IppDateTime::parse_rfc3339(s) {
+        return Ok(IppValue::DateTime(dt));
+    }
+    if s.contains("://") {
+        return Ok(IppValue::Uri(s.to_string()));
+    }
+    Ok(IppValue::Keyword(s.to_string()))
+}
+
+// item of a mixed 1setOf, given as `syntax:value` or just the syntax of an out-of-band value
+fn parse_mixed_item(s: &str) -> Result<IppValue> {
+    match s.find(':') {
+        Some(pos) => parse_item(&s[..pos], &s[pos + 1..]),
+        None => parse_item(s, "")
+    }
+}
+
+fn parse_item(syntax: &str, s: &str) -> Result<IppValue> {
+    // raw tags are given in hex, for example 0x7f
+    if let Some(hex) = syntax.strip_prefix("0x") {
+        let tag = u8::from_str_radix(hex, 16).map_err(|_| invalid("tag", syntax))?;
+        return IppValue::from_bytes(tag, &parse_hex(syntax, s)?);
+    }
+    // ipptool also accepts the short names of the string syntaxes
+    let tag = match syntax {
+        "text" => Some(ValueTag::TextWithoutLanguage),
+        "name" => Some(ValueTag::NameWithoutLanguage),
+        _ => ValueTag::from_name(syntax)
+    };
+    let tag = tag.ok_or_else(|| IppError::ValueError(format!("Unknown syntax '{}'", syntax)))?;
+    let retval = match tag {
+        ValueTag::Integer => IppValue::Integer(parse_integer(syntax, s)?),
+        ValueTag::Enum => IppValue::Enum(parse_integer(syntax, s)?),
+        ValueTag::Boolean => match s {
+            "true" => IppValue::Boolean(true),
+            "false" => IppValue::Boolean(false),
+            _ => return Err(invalid(syntax, s))
+        },
+        ValueTag::RangeOfInteger => {
+            let (min, max) = parse_range(s)?;
+            IppValue::RangeOfInteger(min, max)
+        }
+        ValueTag::Resolution => {
+            let (crossfeed, feed, units) = parse_resolution(s)?;
+            IppValue::Resolution(crossfeed, feed, units)
+        }
+        ValueTag::DateTime => IppValue::DateTime(IppDateTime::parse_rfc3339(s)?),
+        ValueTag::OctectStringUnspecified => if s.starts_with('<') {
+            IppValue::OctetString(parse_hex(syntax, s)?)
+        } else {
+            IppValue::OctetString(parse_string(syntax, s)?.into_bytes())
+        },
+        ValueTag::TextWithoutLanguage => IppValue::TextWithoutLanguage(parse_string(syntax, s)?),
+        ValueTag::NameWithoutLanguage => IppValue::NameWithoutLanguage(parse_string(syntax, s)?),
+        ValueTag::Keyword => IppValue::Keyword(parse_string(syntax, s)?),
+        ValueTag::Uri => IppValue::Uri(parse_string(syntax, s)?),
+        ValueTag::UriScheme => IppValue::UriScheme(parse_string(syntax, s)?),
+        ValueTag::Charset => IppValue::Charset(parse_string(syntax, s)?),
+        ValueTag::NaturalLanguage => IppValue::NaturalLanguage(parse_string(syntax, s)?),
+        ValueTag::MimeMediaType => IppValue::MimeMediaType(parse_string(syntax, s)?),
+        ValueTag::MemberAttrName => IppValue::MemberAttrName(parse_string(syntax, s)?),
+        ValueTag::TextWithLanguage => {
+            let (language, text) = parse_with_language(syntax, s)?;
+            IppValue::TextWithLanguage { language, text }
+        }
+        ValueTag::NameWithLanguage => {
+            let (language, name) = parse_with_language(syntax, s)?;
+            IppValue::NameWithLanguage { language, name }
+        }
+        ValueTag::BegCollection => parse_collection(s)?,
+        ValueTag::Extension => {
+            let data = parse_hex(syntax, s)?;
+            IppValue::from_bytes(ValueTag::Extension as u8, &data)?
+        }
+        ValueTag::Unsupported => IppValue::Unsupported,
+        ValueTag::Unknown => IppValue::Unknown,
+        ValueTag::NoValue => IppValue::NoValue,
+        ValueTag::NotSettable => IppValue::NotSettable,
+        ValueTag::DeleteAttribute => IppValue::DeleteAttribute,
+        ValueTag::AdminDefine => IppValue::AdminDefine,
+        _ => return Err(IppError::ValueError(format!("Unsupported syntax '{}'", syntax)))
+    };
+    Ok(retval)
+}
+
+impl IppValue {
+    /// Write the value with its syntax, for example `1setOf keyword one,two`, using the
+    /// extended notation where ipptool would lose the syntax. `str::parse` reads it back.
+    pub fn to_text(&self) -> String {
+        if self.is_out_of_band() {
+            return self.syntax().to_string();
+        }
+        let prefix = if let IppValue::ListOf(_) = *self { "1setOf " } else { "" };
+        format!("{}{} {}", prefix, item_syntax(self), to_text(self, false, true))
+    }
+
+    /// Parse value from ipptool notation given its syntax name, for example
+    /// `IppValue::parse("rangeOfInteger", "1-10")`.
+    /// Several comma-separated values are returned as `ListOf`, the syntax may carry
+    /// an optional `1setOf` prefix. With the syntax `mixed` each value carries its own
+    /// syntax, for example `keyword:a4,name:custom,no-value`.
+    pub fn parse(syntax: &str, text: &str) -> Result<IppValue> {
+        let syntax = syntax.trim();
+        let syntax = syntax.strip_prefix("1setOf").map_or(syntax, str::trim_start);
+        parse_member(Some(syntax), text.trim())
+    }
+
+    /// Parse collection member value in ipptool notation, inferring the syntax from the text:
+    /// integer, boolean, rangeOfInteger, resolution, dateTime, uri, collection, quoted text
+    /// or keyword
+    pub fn infer(text: &str) -> Result<IppValue> {
+        parse_member(None, text.trim())
+    }
+}
+
+/// Parse value with syntax prefix as written by `IppValue::to_text`, for example `integer 5`
+impl FromStr for IppValue {
+    type Err = IppError;
+
+    fn from_str(s: &str) -> Result<IppValue> {
+        let s = s.trim_start();
+        let mut end = s.find(char::is_whitespace).unwrap_or(s.len());
+        if &s[..end] == "1setOf" {
+            let rest = s[end..].trim_start();
+            end = s.len() - rest.len() + rest.find(char::is_whitespace).unwrap_or(rest.len());
+        }
+        IppValue::parse(&s[..end], &s[end..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(value: IppValue) {
+        let text = value.to_text();
+        let parsed: IppValue = text.parse().unwrap_or_else(|e| panic!("{}: {}", text, e));
+        assert_eq!(tags(&parsed), tags(&value), "{}", text);
+        assert_eq!(parsed.to_text(), text);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        round_trip(IppValue::Integer(5));
+        round_trip(IppValue::RangeOfInteger(-1, 10));
+        round_trip(IppValue::Resolution(600, 300, 3));
+        round_trip(IppValue::Resolution(100, 100, -5));
+        round_trip(IppValue::ListOf(vec![IppValue::Keyword("one".into()), IppValue::Keyword("two".into())]));
+        round_trip(IppValue::TextWithLanguage { language: "en".into(), text: "a, \"b\"".into() });
+        round_trip(IppValue::Collection(vec![
+            ("media-size".into(), IppValue::Collection(vec![
+                ("x-dimension".into(), IppValue::Integer(21000)),
+                ("y-dimension".into(), IppValue::Integer(29700))])),
+            ("media-key".into(), IppValue::NameWithoutLanguage("my media".into()))]));
+    }
+
+    #[test]
+    fn test_mixed_set() {
+        let value = IppValue::ListOf(vec![
+            IppValue::Keyword("a4".into()),
+            IppValue::NameWithoutLanguage("custom".into()),
+            IppValue::NoValue]);
+        assert_eq!(value.to_text(), "1setOf mixed keyword:a4,nameWithoutLanguage:custom,no-value");
+        assert_eq!(value.to_string(), "a4,custom,no-value");
+        round_trip(value.clone());
+        round_trip(IppValue::Collection(vec![("media".into(), value)]));
+    }
+
+    #[test]
+    fn test_resolution_units() {
+        assert_eq!(IppValue::parse("resolution", "300u127").unwrap().to_tag(), ValueTag::Resolution as u8);
+        assert!(IppValue::parse("resolution", "300u128").is_err());
+        assert!(IppValue::parse("resolution", "300u-129").is_err());
+    }
+
+    #[test]
+    fn test_ipptool_notation() {
+        assert_eq!(IppValue::RangeOfInteger(1, 10).to_string(), "1-10");
+        assert_eq!(IppValue::Resolution(600, 600, 3).to_string(), "600dpi");
+        assert_eq!(IppValue::Resolution(600, 300, 4).to_string(), "600x300dpcm");
+        assert_eq!(IppValue::Resolution(100, 100, 7).to_string(), "100dpcm");
+        assert_eq!(IppValue::Resolution(100, 100, 7).to_text(), "resolution 100u7");
+        assert_eq!(IppValue::keywords(&["one", "two"]).to_string(), "one,two");
+        assert_eq!(IppValue::Other(0x7f, vec![0x01]).to_string(), "<01>");
+        assert_eq!(IppValue::Other(0x7f, vec![0x01]).to_text(), "0x7f <01>");
+        assert_eq!(IppValue::NoValue.to_string(), "no-value");
+
+        let media_col = IppValue::Collection(vec![
+            ("media-size".into(), IppValue::Collection(vec![
+                ("x-dimension".into(), IppValue::Integer(21000)),
+                ("y-dimension".into(), IppValue::Integer(29700))])),
+            ("media-key".into(), IppValue::NameWithoutLanguage("a4".into()))]);
+        assert_eq!(media_col.to_string(), "{media-size={x-dimension=21000 y-dimension=29700} media-key=a4}");
+        assert_eq!(media_col.to_text(),
+                   "collection {media-size={x-dimension=21000 y-dimension=29700} media-key:nameWithoutLanguage=a4}");
+    }
+}
@@ -1,23 +1,90 @@
 //!
 //! IPP value
 //!
-use std::io::{Read, Write};
-use std::fmt;
+use std::io::{self, Read, Write};
+use std::str;
 use byteorder::{WriteBytesExt, ReadBytesExt, BigEndian};
 
 use enum_primitive::FromPrimitive;
 
-use ::{Result, ReadIppExt};
+use ::{Result, IppError, ReadIppExt};
 use consts::tag::ValueTag;
+use datetime::IppDateTime;
+use charset::IppCharset;
+
+/// Size of the value field for fixed-size syntaxes
+pub fn fixed_length(vtag: u8) -> Option<usize> {
+    match ValueTag::from_u8(vtag) {
+        Some(ValueTag::Integer) | Some(ValueTag::Enum) => Some(4),
+        Some(ValueTag::Boolean) => Some(1),
+        Some(ValueTag::RangeOfInteger) => Some(8),
+        Some(ValueTag::Resolution) => Some(9),
+        Some(ValueTag::DateTime) => Some(11),
+        _ => None
+    }
+}
+
+/// Largest length which fits into the 16-bit length fields of the wire format
+pub const MAX_LENGTH: usize = 0xffff;
+
+/// Maximum length of attribute and member names (keyword syntax) allowed by RFC 8011
+pub const MAX_NAME_LENGTH: usize = 255;
+
+pub(crate) fn check_length(attribute: &str, length: usize, limit: usize) -> Result<()> {
+    if length > limit {
+        Err(IppError::LengthError { attribute: attribute.to_string(), length, limit })
+    } else {
+        Ok(())
+    }
+}
+
+// fill in the attribute name for length errors raised by the value
+pub(crate) fn with_attribute(error: IppError, name: &str) -> IppError {
+    match error {
+        IppError::LengthError { ref attribute, length, limit } if attribute.is_empty() =>
+            IppError::LengthError { attribute: name.to_string(), length, limit },
+        e => e
+    }
+}
+
+// textWithLanguage and nameWithLanguage values hold two length-prefixed strings:
+// the natural language followed by the text itself
+pub(crate) fn split_with_language(mut data: &[u8]) -> Result<(&[u8], &[u8])> {
+    fn take<'a>(data: &mut &'a [u8]) -> Result<&'a [u8]> {
+        let len = data.read_u16::<BigEndian>()? as usize;
+        if data.len() < len {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        let (retval, rest) = data.split_at(len);
+        *data = rest;
+        Ok(retval)
+    }
+
+    let language = take(&mut data)?;
+    let text = take(&mut data)?;
+
+    if !data.is_empty() {
+        return Err(IppError::AttributeError("Invalid value length for string with language".to_string()));
+    }
+    Ok((language, text))
+}
+
+fn read_with_language(data: &[u8], charset: IppCharset) -> Result<(String, String)> {
+    let (language, text) = split_with_language(data)?;
+    Ok((String::from_utf8_lossy(language).into_owned(), charset.decode(text)))
+}
 
 /// Currently supported IPP values
 #[derive(Clone, Debug)]
 pub enum IppValue {
     Integer(i32),
     Enum(i32),
-    OctetString(String),
+    /// octetString value as raw bytes, see `octet_string_from_str` and `as_octet_str` for text
+    OctetString(Vec<u8>),
     TextWithoutLanguage(String),
     NameWithoutLanguage(String),
+    TextWithLanguage { language: String, text: String },
+    NameWithLanguage { language: String, name: String },
     Charset(String),
     NaturalLanguage(String),
     Uri(String),
@@ -25,18 +92,35 @@ pub enum IppValue {
     Boolean(bool),
     Keyword(String),
     ListOf(Vec<IppValue>),
-    Collection(Vec<IppValue>),
+    /// Collection members as (member name, member value) pairs in wire order
+    Collection(Vec<(String, IppValue)>),
     MimeMediaType(String),
-    DateTime(u16, u8, u8, u8, u8, u8, u8, char, u8, u8),
+    DateTime(IppDateTime),
     MemberAttrName(String),
     Resolution(i32, i32, i8),
+    UriScheme(String),
+    /// Out-of-band 'unsupported' value
+    Unsupported,
+    /// Out-of-band 'unknown' value
+    Unknown,
+    /// Out-of-band 'no-value' value
+    NoValue,
+    /// Out-of-band 'not-settable' value
+    NotSettable,
+    /// Out-of-band 'delete-attribute' value
+    DeleteAttribute,
+    /// Out-of-band 'admin-define' value
+    AdminDefine,
+    /// Value with extension tag 0x7f: 32-bit extended tag and raw value bytes
+    Extension(u32, Vec<u8>),
+    /// Value with unrecognized tag, kept as raw bytes
     Other(u8, Vec<u8>),
 }
 
 impl IppValue {
     /// Convert to binary tag
-    pub fn to_tag(&self) -> ValueTag {
-        match *self {
+    pub fn to_tag(&self) -> u8 {
+        let tag = match *self {
             IppValue::Integer(_) => ValueTag::Integer,
             IppValue::Enum(_) => ValueTag::Enum,
             IppValue::RangeOfInteger(_, _) => ValueTag::RangeOfInteger,
@@ -45,87 +129,252 @@ impl IppValue {
             IppValue::OctetString(_) => ValueTag::OctectStringUnspecified,
             IppValue::TextWithoutLanguage(_) => ValueTag::TextWithoutLanguage,
             IppValue::NameWithoutLanguage(_) => ValueTag::NameWithoutLanguage,
+            IppValue::TextWithLanguage { .. } => ValueTag::TextWithLanguage,
+            IppValue::NameWithLanguage { .. } => ValueTag::NameWithLanguage,
             IppValue::Charset(_) => ValueTag::Charset,
             IppValue::NaturalLanguage(_) => ValueTag::NaturalLanguage,
             IppValue::Uri(_) => ValueTag::Uri,
+            IppValue::UriScheme(_) => ValueTag::UriScheme,
             IppValue::MimeMediaType(_) => ValueTag::MimeMediaType,
-            IppValue::ListOf(ref list) => list[0].to_tag(),
+            IppValue::ListOf(ref list) => return list.first().map_or(ValueTag::NoValue as u8, |v| v.to_tag()),
             IppValue::Collection(_) => ValueTag::BegCollection,
             IppValue::DateTime(..) => ValueTag::DateTime,
             IppValue::MemberAttrName(_) => ValueTag::MemberAttrName,
             IppValue::Resolution(..) => ValueTag::Resolution,
-            IppValue::Other(..) => ValueTag::Unknown,
+            IppValue::Unsupported => ValueTag::Unsupported,
+            IppValue::Unknown => ValueTag::Unknown,
+            IppValue::NoValue => ValueTag::NoValue,
+            IppValue::NotSettable => ValueTag::NotSettable,
+            IppValue::DeleteAttribute => ValueTag::DeleteAttribute,
+            IppValue::AdminDefine => ValueTag::AdminDefine,
+            IppValue::Extension(..) => ValueTag::Extension,
+            IppValue::Other(tag, _) => return tag,
+        };
+        tag as u8
+    }
+
+    /// Syntax name of the value, `1setOf` for lists
+    pub fn syntax(&self) -> &'static str {
+        match *self {
+            IppValue::ListOf(_) => "1setOf",
+            _ => ValueTag::from_u8(self.to_tag()).map_or("unknown", ValueTag::name)
+        }
+    }
+
+    /// Check whether this is an out-of-band value
+    pub fn is_out_of_band(&self) -> bool {
+        matches!(*self,
+            IppValue::Unsupported | IppValue::Unknown | IppValue::NoValue |
+            IppValue::NotSettable | IppValue::DeleteAttribute | IppValue::AdminDefine)
+    }
+
+    /// Create octetString value from text
+    pub fn octet_string_from_str(s: &str) -> IppValue {
+        IppValue::OctetString(s.as_bytes().to_vec())
+    }
+
+    /// Return octetString value as text if it holds valid UTF-8
+    pub fn as_octet_str(&self) -> Option<&str> {
+        match *self {
+            IppValue::OctetString(ref vec) => str::from_utf8(vec).ok(),
+            _ => None
+        }
+    }
+
+    /// Get collection member value by name
+    pub fn member(&self, name: &str) -> Option<&IppValue> {
+        match *self {
+            IppValue::Collection(ref members) =>
+                members.iter().find(|(k, _)| k == name).map(|(_, v)| v),
+            _ => None
+        }
+    }
+
+    /// Length of the encoded value field. 1setOf values and collections span
+    /// several fields and return 0, their items are measured separately.
+    pub fn encoded_length(&self) -> usize {
+        match *self {
+            IppValue::Integer(_) | IppValue::Enum(_) => 4,
+            IppValue::RangeOfInteger(..) => 8,
+            IppValue::Boolean(_) => 1,
+            IppValue::DateTime(..) => 11,
+            IppValue::Resolution(..) => 9,
+            IppValue::Keyword(ref s) |
+            IppValue::TextWithoutLanguage(ref s) | IppValue::NameWithoutLanguage(ref s) |
+            IppValue::Charset(ref s) | IppValue::NaturalLanguage(ref s) |
+            IppValue::Uri(ref s) | IppValue::MimeMediaType(ref s) |
+            IppValue::MemberAttrName(ref s) | IppValue::UriScheme(ref s) => s.len(),
+            IppValue::TextWithLanguage { ref language, text: ref s } |
+            IppValue::NameWithLanguage { ref language, name: ref s } => 4 + language.len() + s.len(),
+            IppValue::OctetString(ref vec) | IppValue::Other(_, ref vec) => vec.len(),
+            IppValue::Extension(_, ref vec) => 4 + vec.len(),
+            _ => 0
+        }
+    }
+
+    /// Maximum length of the value allowed by RFC 8011 for its syntax, if any
+    pub fn max_length(&self) -> Option<usize> {
+        match *self {
+            IppValue::TextWithoutLanguage(_) | IppValue::Uri(_) | IppValue::OctetString(_) => Some(1023),
+            IppValue::NameWithoutLanguage(_) | IppValue::Keyword(_) |
+            IppValue::MimeMediaType(_) | IppValue::MemberAttrName(_) => Some(255),
+            IppValue::Charset(_) | IppValue::NaturalLanguage(_) | IppValue::UriScheme(_) => Some(63),
+            // language-tagged strings also carry the 4 bytes of nested lengths
+            IppValue::TextWithLanguage { .. } => Some(4 + 63 + 1023),
+            IppValue::NameWithLanguage { .. } => Some(4 + 63 + 255),
+            _ => None
+        }
+    }
+
+    /// Check that the value can be encoded. 1setOf values must be non-empty and flat
+    /// and every length must fit into its 16-bit length field.
+    /// Text and name values are measured in UTF-8, an upper bound of their length in every
+    /// supported charset: us-ascii and iso-8859-1 encode each character as a single byte.
+    pub fn check_encodable(&self) -> Result<()> {
+        match *self {
+            IppValue::ListOf(ref list) => {
+                if list.is_empty() {
+                    return Err(IppError::AttributeError("Empty 1setOf value".to_string()));
+                }
+                for item in list {
+                    if let IppValue::ListOf(_) = *item {
+                        return Err(IppError::AttributeError("Nested 1setOf value".to_string()));
+                    }
+                    item.check_encodable()?;
+                }
+                Ok(())
+            }
+            IppValue::Collection(ref members) => {
+                for (name, value) in members {
+                    check_length(name, name.len(), MAX_LENGTH)?;
+                    value.check_encodable().map_err(|e| with_attribute(e, name))?;
+                }
+                Ok(())
+            }
+            _ => check_length("", self.encoded_length(), MAX_LENGTH)
+        }
+    }
+
+    /// Check the value against the RFC 8011 maximum lengths of its syntax
+    pub fn check_limits(&self) -> Result<()> {
+        match *self {
+            IppValue::ListOf(ref list) => {
+                for item in list {
+                    item.check_limits()?;
+                }
+                Ok(())
+            }
+            IppValue::Collection(ref members) => {
+                for (name, value) in members {
+                    check_length(name, name.len(), MAX_NAME_LENGTH)?;
+                    value.check_limits().map_err(|e| with_attribute(e, name))?;
+                }
+                Ok(())
+            }
+            _ => match self.max_length() {
+                Some(limit) => check_length("", self.encoded_length(), limit),
+                None => Ok(())
+            }
         }
     }
 
     /// Read value from binary stream
-    pub fn read(vtag: u8, reader: &mut Read) -> Result<IppValue> {
+    pub fn read(vtag: u8, reader: &mut dyn Read) -> Result<IppValue> {
         let vsize = reader.read_u16::<BigEndian>()?;
+        let data = reader.read_vec(vsize as usize)?;
+        IppValue::from_bytes(vtag, &data)
+    }
 
+    /// Decode value from the bytes of its value field.
+    /// Fixed-size values with a wrong length are kept as `Other` so the stream stays in sync.
+    pub fn from_bytes(vtag: u8, data: &[u8]) -> Result<IppValue> {
+        IppValue::from_bytes_with_charset(vtag, data, IppCharset::Utf8)
+    }
+
+    /// Decode value from the bytes of its value field, text and name values are decoded
+    /// using the given charset
+    pub fn from_bytes_with_charset(vtag: u8, data: &[u8], charset: IppCharset) -> Result<IppValue> {
         let ipptag = match ValueTag::from_u8(vtag) {
             Some(x) => x,
             None => {
-                return Ok(IppValue::Other(vtag, reader.read_vec(vsize as usize)?));
+                return Ok(IppValue::Other(vtag, data.to_vec()));
             }
         };
 
+        if let Some(expected) = fixed_length(vtag) {
+            if data.len() != expected {
+                return Ok(IppValue::Other(vtag, data.to_vec()));
+            }
+        }
+
+        let vsize = data.len();
+        let mut reader = data;
+
         match ipptag {
             ValueTag::Integer => {
-                debug_assert_eq!(vsize, 4);
                 Ok(IppValue::Integer(reader.read_i32::<BigEndian>()?))
             }
             ValueTag::Enum => {
-                debug_assert_eq!(vsize, 4);
                 Ok(IppValue::Enum(reader.read_i32::<BigEndian>()?))
             }
             ValueTag::OctectStringUnspecified => {
-                Ok(IppValue::OctetString(reader.read_string(vsize as usize)?))
+                Ok(IppValue::OctetString(data.to_vec()))
             }
             ValueTag::TextWithoutLanguage => {
-                Ok(IppValue::TextWithoutLanguage(reader.read_string(vsize as usize)?))
+                Ok(IppValue::TextWithoutLanguage(charset.decode(data)))
             }
             ValueTag::NameWithoutLanguage => {
-                Ok(IppValue::NameWithoutLanguage(reader.read_string(vsize as usize)?))
+                Ok(IppValue::NameWithoutLanguage(charset.decode(data)))
+            }
+            ValueTag::TextWithLanguage => {
+                let (language, text) = read_with_language(data, charset)?;
+                Ok(IppValue::TextWithLanguage { language, text })
+            }
+            ValueTag::NameWithLanguage => {
+                let (language, name) = read_with_language(data, charset)?;
+                Ok(IppValue::NameWithLanguage { language, name })
             }
             ValueTag::Charset => {
-                Ok(IppValue::Charset(reader.read_string(vsize as usize)?))
+                Ok(IppValue::Charset(reader.read_string(vsize)?))
             }
             ValueTag::NaturalLanguage => {
-                Ok(IppValue::NaturalLanguage(reader.read_string(vsize as usize)?))
+                Ok(IppValue::NaturalLanguage(reader.read_string(vsize)?))
             }
             ValueTag::Uri => {
-                Ok(IppValue::Uri(reader.read_string(vsize as usize)?))
-            }
+                Ok(IppValue::Uri(reader.read_string(vsize)?))
+            }
+            ValueTag::UriScheme => {
+                Ok(IppValue::UriScheme(reader.read_string(vsize)?))
+            }
+            // out-of-band values have no data, anything the sender put there is ignored
+            ValueTag::Unsupported => Ok(IppValue::Unsupported),
+            ValueTag::Unknown => Ok(IppValue::Unknown),
+            ValueTag::NoValue => Ok(IppValue::NoValue),
+            ValueTag::NotSettable => Ok(IppValue::NotSettable),
+            ValueTag::DeleteAttribute => Ok(IppValue::DeleteAttribute),
+            ValueTag::AdminDefine => Ok(IppValue::AdminDefine),
             ValueTag::RangeOfInteger => {
-                debug_assert_eq!(vsize, 8);
                 Ok(IppValue::RangeOfInteger(reader.read_i32::<BigEndian>()?,
                                              reader.read_i32::<BigEndian>()?))
             }
             ValueTag::Boolean => {
-                debug_assert_eq!(vsize, 1);
                 Ok(IppValue::Boolean(reader.read_u8()? != 0))
             }
             ValueTag::Keyword => {
-                Ok(IppValue::Keyword(reader.read_string(vsize as usize)?))
+                Ok(IppValue::Keyword(reader.read_string(vsize)?))
             }
             ValueTag::MimeMediaType => {
-                Ok(IppValue::MimeMediaType(reader.read_string(vsize as usize)?))
+                Ok(IppValue::MimeMediaType(reader.read_string(vsize)?))
             }
             ValueTag::DateTime => {
-                Ok(IppValue::DateTime(
-                    reader.read_u16::<BigEndian>()?,
-                    reader.read_u8()?,
-                    reader.read_u8()?,
-                    reader.read_u8()?,
-                    reader.read_u8()?,
-                    reader.read_u8()?,
-                    reader.read_u8()?,
-                    reader.read_u8()? as char,
-                    reader.read_u8()?,
-                    reader.read_u8()?))
+                // out of range fields are kept as raw bytes
+                match IppDateTime::from_bytes(data) {
+                    Ok(dt) => Ok(IppValue::DateTime(dt)),
+                    Err(_) => Ok(IppValue::Other(vtag, data.to_vec()))
+                }
             }
             ValueTag::MemberAttrName => {
-                Ok(IppValue::MemberAttrName(reader.read_string(vsize as usize)?))
+                Ok(IppValue::MemberAttrName(reader.read_string(vsize)?))
             }
             ValueTag::Resolution => {
                 Ok(IppValue::Resolution(
@@ -133,14 +382,33 @@ impl IppValue {
                     reader.read_i32::<BigEndian>()?,
                     reader.read_i8()?))
             }
+            ValueTag::Extension => {
+                // extended tag is stored in the first four bytes of the value
+                if vsize < 4 {
+                    return Err(IppError::AttributeError("Invalid value length for extension tag".to_string()));
+                }
+                let ext_tag = reader.read_u32::<BigEndian>()?;
+                Ok(IppValue::Extension(ext_tag, reader.to_vec()))
+            }
             _ => {
-                Ok(IppValue::Other(vtag, reader.read_vec(vsize as usize)?))
+                Ok(IppValue::Other(vtag, data.to_vec()))
             }
         }
     }
 
     /// Write value to binary stream
-    pub fn write(&self, writer: &mut Write) -> Result<usize> {
+    pub fn write(&self, writer: &mut dyn Write) -> Result<usize> {
+        self.write_with_charset(writer, IppCharset::Utf8)
+    }
+
+    /// Write value to binary stream, text and name values are encoded using the given charset
+    pub fn write_with_charset(&self, writer: &mut dyn Write, charset: IppCharset) -> Result<usize> {
+        self.check_encodable()?;
+        self.write_unchecked(writer, charset)
+    }
+
+    /// Write value which already passed `check_encodable`, nested values are not checked again
+    pub(crate) fn write_unchecked(&self, writer: &mut dyn Write, charset: IppCharset) -> Result<usize> {
         match *self {
             IppValue::Integer(i) | IppValue::Enum(i) => {
                 writer.write_u16::<BigEndian>(4)?;
@@ -158,65 +426,91 @@ impl IppValue {
                 writer.write_u8(if b {1} else {0})?;
                 Ok(3)
             }
-            IppValue::Keyword(ref s) | IppValue::OctetString(ref s) |
-            IppValue::TextWithoutLanguage(ref s) | IppValue::NameWithoutLanguage(ref s) |
+            IppValue::TextWithoutLanguage(ref s) | IppValue::NameWithoutLanguage(ref s) => {
+                let s = charset.encode(s);
+                writer.write_u16::<BigEndian>(s.len() as u16)?;
+                writer.write_all(&s)?;
+                Ok(2 + s.len())
+            }
+            IppValue::Keyword(ref s) |
             IppValue::Charset(ref s) | IppValue::NaturalLanguage(ref s) |
             IppValue::Uri(ref s) | IppValue::MimeMediaType(ref s) |
-            IppValue::MemberAttrName(ref s) => {
+            IppValue::MemberAttrName(ref s) | IppValue::UriScheme(ref s) => {
                 writer.write_u16::<BigEndian>(s.len() as u16)?;
                 writer.write_all(s.as_bytes())?;
                 Ok(2 + s.len())
             }
+            IppValue::TextWithLanguage { ref language, text: ref s } |
+            IppValue::NameWithLanguage { ref language, name: ref s } => {
+                let s = charset.encode(s);
+                writer.write_u16::<BigEndian>((4 + language.len() + s.len()) as u16)?;
+                writer.write_u16::<BigEndian>(language.len() as u16)?;
+                writer.write_all(language.as_bytes())?;
+                writer.write_u16::<BigEndian>(s.len() as u16)?;
+                writer.write_all(&s)?;
+                Ok(6 + language.len() + s.len())
+            }
             IppValue::ListOf(ref list) => {
+                // the tag of the first value is written by the caller,
+                // additional values carry their own tags and empty names
                 let mut retval = 0;
                 for (i, item) in list.iter().enumerate() {
-                    retval += item.write(writer)?;
-                    if i < list.len() - 1 {
-                        writer.write_u8(self.to_tag() as u8)?;
+                    if i > 0 {
+                        writer.write_u8(item.to_tag())?;
                         writer.write_u16::<BigEndian>(0)?;
                         retval += 3;
                     }
+                    retval += item.write_unchecked(writer, charset)?;
                 }
                 Ok(retval)
             }
-            IppValue::Collection(ref list) => {
-                let mut retval = 0;
-                for (i, item) in list.iter().enumerate() {
-                    retval += item.write(writer)?;
-                    if i < list.len() - 1 {
-                        writer.write_u8(self.to_tag() as u8)?;
-                        writer.write_u16::<BigEndian>(0)?;
-                        retval += 3;
-                    }
+            IppValue::Collection(ref members) => {
+                // begCollection value is empty
+                writer.write_u16::<BigEndian>(0)?;
+                let mut retval = 2;
+
+                for (name, value) in members {
+                    writer.write_u8(ValueTag::MemberAttrName as u8)?;
+                    writer.write_u16::<BigEndian>(0)?;
+                    writer.write_u16::<BigEndian>(name.len() as u16)?;
+                    writer.write_all(name.as_bytes())?;
+                    retval += 5 + name.len();
+
+                    writer.write_u8(value.to_tag())?;
+                    writer.write_u16::<BigEndian>(0)?;
+                    retval += 3 + value.write_unchecked(writer, charset)?;
                 }
+
                 writer.write_u8(ValueTag::EndCollection as u8)?;
-                retval += 1;
+                writer.write_u16::<BigEndian>(0)?;
+                writer.write_u16::<BigEndian>(0)?;
+                retval += 5;
+
                 Ok(retval)
             }
-            IppValue::DateTime(year, month, day, hour, minutes, seconds, deciseconds, utcdir, utchours, utcmins) => {
+            IppValue::DateTime(ref dt) => {
                 writer.write_u16::<BigEndian>(11)?;
-
-                writer.write_u16::<BigEndian>(year)?;
-                writer.write_u8(month)?;
-                writer.write_u8(day)?;
-                writer.write_u8(hour)?;
-                writer.write_u8(minutes)?;
-                writer.write_u8(seconds)?;
-                writer.write_u8(deciseconds)?;
-                writer.write_u8(utcdir as u8)?;
-                writer.write_u8(utchours)?;
-                writer.write_u8(utcmins)?;
-
-                Ok(13)
+                Ok(2 + dt.write(writer)?)
             }
             IppValue::Resolution(crossfeed, feed, units) => {
                 writer.write_u16::<BigEndian>(9)?;
                 writer.write_i32::<BigEndian>(crossfeed)?;
                 writer.write_i32::<BigEndian>(feed)?;
                 writer.write_i8(units)?;
-                Ok(9)
+                Ok(11)
             }
-            IppValue::Other(_, ref vec) => {
+            IppValue::Unsupported | IppValue::Unknown | IppValue::NoValue |
+            IppValue::NotSettable | IppValue::DeleteAttribute | IppValue::AdminDefine => {
+                writer.write_u16::<BigEndian>(0)?;
+                Ok(2)
+            }
+            IppValue::Extension(ext_tag, ref vec) => {
+                writer.write_u16::<BigEndian>((4 + vec.len()) as u16)?;
+                writer.write_u32::<BigEndian>(ext_tag)?;
+                writer.write_all(vec)?;
+                Ok(6 + vec.len())
+            }
+            IppValue::OctetString(ref vec) | IppValue::Other(_, ref vec) => {
                 writer.write_u16::<BigEndian>(vec.len() as u16)?;
                 writer.write_all(vec)?;
                 Ok(2 + vec.len())
@@ -225,49 +519,6 @@ impl IppValue {
     }
 }
 
-/// Implement Display trait to print the value
-impl fmt::Display for IppValue {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            IppValue::Integer(i) | IppValue::Enum(i) => {
-                write!(f, "{}", i)
-            }
-            IppValue::RangeOfInteger(min, max) => {
-                write!(f, "{}..{}", min, max)
-            }
-            IppValue::Boolean(b) => {
-                write!(f, "{}", if b {"true"} else {"false"})
-            }
-            IppValue::Keyword(ref s) | IppValue::OctetString(ref s) |
-            IppValue::TextWithoutLanguage(ref s) | IppValue::NameWithoutLanguage(ref s) |
-            IppValue::Charset(ref s) | IppValue::NaturalLanguage(ref s) |
-            IppValue::Uri(ref s) | IppValue::MimeMediaType(ref s) |
-            IppValue::MemberAttrName(ref s) => {
-                write!(f, "{}", s)
-            }
-            IppValue::ListOf(ref list) => {
-                let s: Vec<String> = list.iter().map(|v| format!("{}", v)).collect();
-                write!(f, "[{}]", s.join(", "))
-            }
-            IppValue::Collection(ref list) => {
-                let s: Vec<String> = list.iter().map(|v| format!("{}", v)).collect();
-                write!(f, "<{}>", s.join(", "))
-            }
-            IppValue::DateTime(year, month, day, hour, minutes, seconds, deciseconds, utcdir, utchours, _) => {
-                write!(f, "{}-{}-{},{}:{}:{}.{},{}{}utc", year, month, day, hour,
-                    minutes, seconds, deciseconds, utcdir as char, utchours)
-            }
-            IppValue::Resolution(crossfeed, feed, units) => {
-                write!(f, "{}x{}{}", crossfeed, feed, if units == 3 {"in"} else {"cm"})
-            }
-
-            IppValue::Other(tag, ref vec) => {
-                write!(f, "{:0x}: {:?}", tag, vec)
-            }
-        }
-    }
-}
-
 impl<'a> IntoIterator for &'a IppValue {
     type Item = &'a IppValue;
     type IntoIter = IppValueIntoIterator<'a>;
@@ -287,10 +538,231 @@ impl<'a> Iterator for IppValueIntoIterator<'a> {
     
     fn next(&mut self) -> Option<Self::Item> {
         match *self.value {
-            IppValue::ListOf(ref list) | IppValue::Collection(ref list) =>
+            IppValue::ListOf(ref list) =>
                 if self.index < list.len() { self.index += 1; Some(&list[self.index - 1]) } else { None },
             _ =>
                 if self.index == 0 { self.index += 1; Some(self.value) } else { None }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::*;
+    use attribute::{IppAttribute, IppAttributeList};
+    use consts::tag::DelimiterTag;
+    use parser::{IppParser, ParseErrorKind};
+    use IppHeader;
+
+    fn media_col(media_type: &str, x: i32, y: i32) -> IppValue {
+        IppValue::Collection(vec![
+            ("media-size".to_string(), IppValue::Collection(vec![
+                ("x-dimension".to_string(), IppValue::Integer(x)),
+                ("y-dimension".to_string(), IppValue::Integer(y))])),
+            ("media-type".to_string(), IppValue::Keyword(media_type.to_string()))])
+    }
+
+    fn write_message(attributes: &IppAttributeList) -> Vec<u8> {
+        let mut data = Vec::new();
+        IppHeader::new(0x0101, 0x0002, 1).write(&mut data).unwrap();
+        attributes.write(&mut data).unwrap();
+        data
+    }
+
+    fn parse(data: &[u8]) -> IppAttributeList {
+        let mut cursor = Cursor::new(data);
+        IppParser::new(&mut cursor).parse().unwrap().into_parts().1
+    }
+
+    // message with a utf-8 operation group and the given encoded job attributes
+    fn job_message(attributes: &[u8]) -> Vec<u8> {
+        let mut retval = Vec::new();
+        IppHeader::new(0x0101, 0x0002, 1).write(&mut retval).unwrap();
+        retval.push(DelimiterTag::OperationAttributes as u8);
+        IppAttribute::new("attributes-charset", IppValue::Charset("utf-8".to_string())).write(&mut retval).unwrap();
+        retval.push(DelimiterTag::JobAttributes as u8);
+        retval.extend_from_slice(attributes);
+        retval.push(DelimiterTag::EndOfAttributes as u8);
+        retval
+    }
+
+    // encoded value tag with its name and value field
+    fn tag(tag: u8, name: &str, data: &[u8]) -> Vec<u8> {
+        let mut retval = vec![tag];
+        retval.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        retval.extend_from_slice(name.as_bytes());
+        retval.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        retval.extend_from_slice(data);
+        retval
+    }
+
+    #[test]
+    fn test_collection_encoding() {
+        let attr = IppAttribute::new("media-size", IppValue::Collection(vec![
+            ("x-dimension".to_string(), IppValue::Integer(21000))]));
+        let mut data = Vec::new();
+        attr.write(&mut data).unwrap();
+
+        let mut expected = vec![0x34, 0, 10];
+        expected.extend(b"media-size");
+        expected.extend([0, 0, 0x4a, 0, 0, 0, 11]);
+        expected.extend(b"x-dimension");
+        expected.extend([0x21, 0, 0, 0, 4, 0, 0, 0x52, 0x08]);
+        expected.extend([0x37, 0, 0, 0, 0]);
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn test_collection_round_trip() {
+        let mut attributes = IppAttributeList::new();
+        attributes.add(DelimiterTag::OperationAttributes,
+                       IppAttribute::new("attributes-charset", IppValue::Charset("utf-8".to_string())));
+        attributes.add(DelimiterTag::JobAttributes, IppAttribute::new("media-col", media_col("stationery", 21000, 29700)));
+        attributes.add(DelimiterTag::JobAttributes, IppAttribute::new("finishings-col", IppValue::Collection(vec![
+            ("finishing-template".to_string(), IppValue::Keyword("staple".to_string())),
+            ("media-size".to_string(), IppValue::Collection(Vec::new()))])));
+        attributes.add(DelimiterTag::PrinterAttributes, IppAttribute::new("media-col-database", IppValue::ListOf(vec![
+            media_col("stationery", 21000, 29700),
+            media_col("photographic", 10160, 15240)])));
+        let data = write_message(&attributes);
+
+        let mut cursor = Cursor::new(&data);
+        let result = IppParser::new(&mut cursor).parse().unwrap();
+        match result.attributes().get(DelimiterTag::PrinterAttributes, "media-col-database").map(|attr| attr.value()) {
+            Some(IppValue::ListOf(items)) => assert_eq!(items.len(), 2),
+            value => panic!("unexpected value {:?}", value)
+        }
+        assert_eq!(write_message(result.attributes()), data);
+    }
+
+    #[test]
+    fn test_mixed_list_keeps_tags() {
+        let value = IppValue::ListOf(vec![
+            IppValue::Keyword("iso_a4_210x297mm".to_string()),
+            IppValue::NameWithoutLanguage("Custom".to_string()),
+            IppValue::NoValue]);
+        let mut data = Vec::new();
+        IppAttribute::new("media-supported", value).write(&mut data).unwrap();
+        let expected = [
+            tag(ValueTag::Keyword as u8, "media-supported", b"iso_a4_210x297mm"),
+            tag(ValueTag::NameWithoutLanguage as u8, "", b"Custom"),
+            tag(ValueTag::NoValue as u8, "", b"")].concat();
+        assert_eq!(data, expected);
+
+        let data = job_message(&expected);
+        let parsed = parse(&data);
+        let tags: Vec<_> = parsed.get(DelimiterTag::JobAttributes, "media-supported").unwrap()
+            .value().into_iter().map(IppValue::to_tag).collect();
+        assert_eq!(tags, [ValueTag::Keyword as u8, ValueTag::NameWithoutLanguage as u8, ValueTag::NoValue as u8]);
+        assert_eq!(write_message(&parsed), data);
+    }
+
+    #[test]
+    fn test_empty_list_is_rejected() {
+        let value = IppValue::ListOf(Vec::new());
+        assert_eq!(value.to_tag(), ValueTag::NoValue as u8);
+        assert!(value.check_encodable().is_err());
+
+        let mut data = Vec::new();
+        assert!(value.write(&mut data).is_err());
+        assert!(IppAttribute::new("media-supported", value).write(&mut data).is_err());
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn test_with_language_encoding() {
+        let value = IppValue::TextWithLanguage { language: "de".to_string(), text: "Büro".to_string() };
+        let mut data = Vec::new();
+        assert_eq!(value.write(&mut data).unwrap(), 13);
+        let mut expected = vec![0, 11, 0, 2, b'd', b'e', 0, 5];
+        expected.extend_from_slice("Büro".as_bytes());
+        assert_eq!(data, expected);
+
+        let name = IppValue::NameWithLanguage { language: "en".to_string(), name: "report".to_string() };
+        let data = job_message(&[
+            tag(ValueTag::TextWithLanguage as u8, "job-message-from-operator", &expected[2..]),
+            tag(ValueTag::NameWithLanguage as u8, "job-name", &[0, 2, b'e', b'n', 0, 6, b'r', b'e', b'p', b'o', b'r', b't'])
+        ].concat());
+        let parsed = parse(&data);
+        let get = |name| parsed.get(DelimiterTag::JobAttributes, name).unwrap().value();
+        assert_eq!(format!("{:?}", get("job-message-from-operator")), format!("{:?}", value));
+        assert_eq!(format!("{:?}", get("job-name")), format!("{:?}", name));
+        assert_eq!(write_message(&parsed), data);
+    }
+
+    #[test]
+    fn test_with_language_truncated() {
+        // inner text length exceeds the value, or leaves bytes over
+        for data in [&[0, 2, b'e', b'n', 0, 5, b'x'][..], &[0, 2, b'e', b'n'], &[0, 9, b'e'], &[0],
+                     &[0, 2, b'e', b'n', 0, 1, b'x', b'y']].iter() {
+            assert!(IppValue::from_bytes(ValueTag::TextWithLanguage as u8, data).is_err());
+            assert!(IppValue::from_bytes(ValueTag::NameWithLanguage as u8, data).is_err());
+
+            let message = job_message(&tag(ValueTag::TextWithLanguage as u8, "job-name", data));
+            let mut cursor = Cursor::new(&message);
+            match IppParser::new(&mut cursor).parse() {
+                Err(IppError::ParseError(e)) => assert!(matches!(e.kind, ParseErrorKind::InvalidValue(_))),
+                _ => panic!("truncated value {:?} was accepted", data)
+            }
+        }
+    }
+
+    #[test]
+    fn test_out_of_band_and_uri_scheme_tags() {
+        let values = [
+            (ValueTag::Unsupported, &b""[..], IppValue::Unsupported),
+            (ValueTag::Unknown, b"", IppValue::Unknown),
+            (ValueTag::NoValue, b"", IppValue::NoValue),
+            (ValueTag::NotSettable, b"", IppValue::NotSettable),
+            (ValueTag::DeleteAttribute, b"", IppValue::DeleteAttribute),
+            (ValueTag::AdminDefine, b"", IppValue::AdminDefine),
+            (ValueTag::UriScheme, b"ipps", IppValue::UriScheme("ipps".to_string()))];
+        for (vtag, field, value) in values.iter() {
+            let encoded = tag(*vtag as u8, "printer-info", field);
+            let mut data = Vec::new();
+            IppAttribute::new("printer-info", value.clone()).write(&mut data).unwrap();
+            assert_eq!(data, encoded, "{:?}", value);
+
+            let message = job_message(&encoded);
+            let parsed = parse(&message);
+            let decoded = parsed.get(DelimiterTag::JobAttributes, "printer-info").unwrap().value();
+            assert_eq!(format!("{:?}", decoded), format!("{:?}", value));
+            assert_eq!(decoded.to_tag(), *vtag as u8);
+            assert_eq!(decoded.is_out_of_band(), *vtag != ValueTag::UriScheme);
+            assert_eq!(write_message(&parsed), message);
+        }
+    }
+
+    #[test]
+    fn test_binary_octet_string() {
+        let bytes = [0xff, 0x00, 0xfe];
+        let data = job_message(&tag(ValueTag::OctectStringUnspecified as u8, "job-password", &bytes));
+        let parsed = parse(&data);
+        let value = parsed.get(DelimiterTag::JobAttributes, "job-password").unwrap().value();
+        match *value {
+            IppValue::OctetString(ref decoded) => assert_eq!(decoded[..], bytes[..]),
+            ref value => panic!("unexpected value {:?}", value)
+        }
+        assert_eq!(value.as_octet_str(), None);
+        assert_eq!(write_message(&parsed), data);
+    }
+
+    #[test]
+    fn test_unknown_and_extension_tags() {
+        let encoded = [
+            tag(0x5f, "vendor-state", &[1, 2, 3]),
+            tag(0xa0, "vendor-blob", &[0xff]),
+            tag(ValueTag::Extension as u8, "vendor-extension", &[0x40, 0, 0, 0x01, 0xaa, 0xbb])].concat();
+        let data = job_message(&encoded);
+        let parsed = parse(&data);
+        let get = |name| parsed.get(DelimiterTag::JobAttributes, name).unwrap().value();
+        assert!(matches!(*get("vendor-state"), IppValue::Other(0x5f, ref data) if data[..] == [1, 2, 3]));
+        assert!(matches!(*get("vendor-blob"), IppValue::Other(0xa0, ref data) if data[..] == [0xff]));
+        assert!(matches!(*get("vendor-extension"), IppValue::Extension(0x4000_0001, ref data) if data[..] == [0xaa, 0xbb]));
+        assert_eq!(write_message(&parsed), data);
+
+        // the extended tag needs four bytes
+        assert!(IppValue::from_bytes(ValueTag::Extension as u8, &[0x40, 0, 0]).is_err());
+    }
+}